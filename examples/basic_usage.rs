@@ -9,11 +9,12 @@ use overgg_scraper::data_structs::MatchBriefType::*;
 fn main() {
     match actual_main() {
         Ok(()) => {},
-        Err(e) => println!("\n{:?}. \nDescription: {:?}\n", e, e.description())
+        Err(e) => println!("\n{:?}. \nDescription: {}\n", e, e)
     }
 }
 
 // We write a function so that we can return a `Result` and use `?`
+#[allow(clippy::get_first, clippy::len_zero)]
 fn actual_main() -> Result<()> {
     let dlr = Downloader::new();
     let scraper = dlr.main_page()?;
@@ -32,21 +33,21 @@ fn actual_main() -> Result<()> {
         let loser = completed_matches[0].loser().unwrap();
 
         println!("Completed match #1: {} beat {} by a score of {} - {}",
-            winner.name,
-            loser.name,
+            winner.name(),
+            loser.name(),
             winner.maps_won.unwrap(),
             loser.maps_won.unwrap());
     }
 
     println!("Upcoming match #1: {} plays {} at {}",
-        upcoming_matches[0].teams[0].name,
-        upcoming_matches[0].teams[1].name,
+        upcoming_matches[0].teams[0].name(),
+        upcoming_matches[0].teams[1].name(),
         upcoming_matches[0].scheduled_time.unwrap());
 
     if let Some(_match) = live_matches.get(0) {
         println!("Live match #1: {} is playing {} and the score is currently {} - {}",
-            _match.teams[0].name,
-            _match.teams[1].name,
+            _match.teams[0].name(),
+            _match.teams[1].name(),
             _match.teams[0].maps_won.unwrap(),
             _match.teams[1].maps_won.unwrap());
     }
@@ -0,0 +1,31 @@
+//! Feeds arbitrary HTML to every scraper.
+//!
+//! Run with `cargo +nightly fuzz run scrapers` from the crate root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use overgg_scraper::data_structs::MatchBriefType::*;
+use overgg_scraper::scrapers::event_page::EventPageScraper;
+use overgg_scraper::scrapers::links::site_links;
+use overgg_scraper::scrapers::main_page::MainPageScraper;
+use overgg_scraper::scrapers::match_page::MatchPageScraper;
+use overgg_scraper::scrapers::team_page::TeamPageScraper;
+use overgg_scraper::site::Site;
+
+fuzz_target!(|data: &[u8]| {
+    let html = String::from_utf8_lossy(data).into_owned();
+
+    let _ = site_links(&html, &Site::default());
+
+    let _ = MatchPageScraper::from(html.clone()).live_state_with_warnings();
+    let _ = TeamPageScraper::from(html.clone()).team_info_with_warnings();
+    let _ = EventPageScraper::from(html.clone()).event_info_with_warnings();
+
+    let scraper = MainPageScraper::from(html);
+
+    for &_type in &[Live, InFuture, Completed] {
+        let _ = scraper.matches_brief_with_warnings(_type);
+    }
+    let _ = scraper.live_streams_with_warnings();
+});
@@ -0,0 +1,312 @@
+//! Saves fetched pages to disk and replays them later.
+//!
+//! An archive is a directory containing the body of every saved page in
+//! `pages/` along with an append-only `index.toml` that records each page's
+//! URL, fetch time, response headers and body file:
+//!
+//! ```toml
+//! [[page]]
+//! url = "https://www.over.gg/"
+//! fetched_at = "2018-08-07T00:00:00+00:00"
+//! headers = [["content-type", "text/html; charset=UTF-8"]]
+//! file = "pages/000001.html"
+//! ```
+//!
+//! Wrap any `Fetcher` in a `Recorder` to archive everything it fetches, and
+//! use `Archive::replay` to run new extractors over old snapshots (including
+//! whole crawls) deterministically.
+
+use crate::error::*;
+use crate::fetch::{Fetcher, Page};
+use chrono::{DateTime, Utc};
+use std::cell::RefCell;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const INDEX_FILE: &str = "index.toml";
+const PAGES_DIR: &str = "pages";
+
+/// The on-disk index.
+#[derive(Debug, Default, serde_derive::Serialize, serde_derive::Deserialize)]
+struct Index {
+    #[serde(default)]
+    page: Vec<Record>
+}
+
+/// An entry in the index.
+#[derive(Debug, Clone, serde_derive::Serialize, serde_derive::Deserialize)]
+struct Record {
+    url: String,
+    /// RFC 3339
+    fetched_at: String,
+    headers: Vec<(String, String)>,
+    /// Relative to the archive directory
+    file: String
+}
+
+/// A directory of saved pages.
+pub struct Archive {
+    dir: PathBuf,
+    records: Vec<Record>
+}
+
+impl Archive {
+    /// Opens the archive in the given directory, creating it if it doesn't
+    /// exist yet.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(dir.join(PAGES_DIR))?;
+
+        let index_path = dir.join(INDEX_FILE);
+        let index: Index = if index_path.exists() {
+            ::toml::from_str(&fs::read_to_string(index_path)?)?
+        } else {
+            Index::default()
+        };
+
+        Ok(Self {
+            dir,
+            records: index.page
+        })
+    }
+
+    /// The number of pages in the archive.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether the archive has no pages in it.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Adds the given page to the archive.
+    ///
+    /// Saving the same URL again adds a new snapshot rather than replacing the
+    /// old one.
+    pub fn save(&mut self, page: &Page) -> Result<()> {
+        let file = self.write_page_file(&page.body)?;
+
+        let record = Record {
+            url: page.url.clone(),
+            fetched_at: page.fetched_at.to_rfc3339(),
+            headers: page.headers.clone(),
+            file
+        };
+
+        // Each record is its own `[[page]]` table, so the index can simply be
+        // appended to
+        let entry = ::toml::to_string(&Index { page: vec![record.clone()] })?;
+        let mut index = OpenOptions::new().create(true).append(true).open(self.dir.join(INDEX_FILE))?;
+        writeln!(index, "{}", entry)?;
+
+        self.records.push(record);
+        Ok(())
+    }
+
+    /// Writes a page body to the first numbered file that doesn't exist yet,
+    /// returning its path relative to the archive directory.
+    ///
+    /// Numbering starts after the pages in the index, but files may exist
+    /// past that (e.g. if saving was cut off before the index was written,
+    /// or another process is saving to the same archive), and those are
+    /// never overwritten.
+    fn write_page_file(&self, body: &str) -> Result<String> {
+        let mut number = self.records.len() + 1;
+
+        loop {
+            let file = format!("{}/{:06}.html", PAGES_DIR, number);
+
+            match OpenOptions::new().write(true).create_new(true).open(self.dir.join(&file)) {
+                Ok(mut out) => {
+                    out.write_all(body.as_bytes())?;
+                    return Ok(file);
+                },
+                Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => number += 1,
+                Err(e) => return Err(e.into())
+            }
+        }
+    }
+
+    /// The fetch times of every snapshot of the given URL, oldest first.
+    pub fn snapshots(&self, url: &str) -> Vec<DateTime<Utc>> {
+        let mut times: Vec<_> = self.records.iter()
+            .filter(|r| r.url == url)
+            .filter_map(|r| parse_time(&r.fetched_at))
+            .collect();
+        times.sort();
+        times
+    }
+
+    /// Gets a `Fetcher` that serves pages from this archive.
+    #[inline]
+    pub fn replay(&self) -> Replay<'_> {
+        Replay {
+            archive: self,
+            as_of: None
+        }
+    }
+
+    /// Loads the latest snapshot of the given URL fetched at or before
+    /// `as_of` (or the latest overall if `None`).
+    fn load(&self, url: &str, as_of: Option<DateTime<Utc>>) -> Result<Option<Page>> {
+        let record = self.records.iter()
+            .filter(|r| r.url == url)
+            .filter_map(|r| parse_time(&r.fetched_at).map(|t| (t, r)))
+            .filter(|&(t, _)| as_of.is_none_or(|as_of| t <= as_of))
+            // Later records win ties, as they were saved later
+            .max_by(|a, b| a.0.cmp(&b.0));
+
+        match record {
+            Some((fetched_at, record)) => Ok(Some(Page {
+                url: record.url.clone(),
+                fetched_at,
+                headers: record.headers.clone(),
+                body: fs::read_to_string(self.dir.join(&record.file))?
+            })),
+            None => Ok(None)
+        }
+    }
+}
+
+fn parse_time(time: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(time).ok().map(|t| t.with_timezone(&Utc))
+}
+
+/// A `Fetcher` that serves pages from an `Archive` instead of the network.
+///
+/// Fetching a URL that isn't in the archive fails with `Error::NotArchived`.
+pub struct Replay<'a> {
+    archive: &'a Archive,
+    as_of: Option<DateTime<Utc>>
+}
+
+impl<'a> Replay<'a> {
+    /// Only serve snapshots fetched at or before the given time, so that the
+    /// site can be replayed as it was at that point.
+    #[inline]
+    pub fn as_of(mut self, time: DateTime<Utc>) -> Self {
+        self.as_of = Some(time);
+        self
+    }
+}
+
+impl<'a> Fetcher for Replay<'a> {
+    fn fetch(&self, url: &str) -> Result<Page> {
+        match self.archive.load(url, self.as_of)? {
+            Some(page) => Ok(page),
+            None => Err(Error::NotArchived(url.into()))
+        }
+    }
+}
+
+/// A `Fetcher` that saves every page fetched by another `Fetcher` to an
+/// `Archive`.
+pub struct Recorder<F> {
+    inner: F,
+    archive: RefCell<Archive>
+}
+
+impl<F: Fetcher> Recorder<F> {
+    #[inline]
+    pub fn new(inner: F, archive: Archive) -> Self {
+        Self {
+            inner,
+            archive: RefCell::new(archive)
+        }
+    }
+
+    /// Gives the archive back.
+    #[inline]
+    pub fn into_archive(self) -> Archive {
+        self.archive.into_inner()
+    }
+}
+
+impl<F: Fetcher> Fetcher for Recorder<F> {
+    fn fetch(&self, url: &str) -> Result<Page> {
+        let page = self.inner.fetch(url)?;
+        self.archive.borrow_mut().save(&page)?;
+        Ok(page)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+    use std::env;
+
+    struct Clock(RefCell<i64>);
+
+    impl Fetcher for Clock {
+        fn fetch(&self, url: &str) -> Result<Page> {
+            let mut now = self.0.borrow_mut();
+            *now += 100;
+
+            Ok(Page {
+                url: url.into(),
+                fetched_at: Utc.timestamp_opt(*now, 0).unwrap(),
+                headers: vec![("content-type".into(), "text/html".into())],
+                body: format!("<p>{} at {}</p>", url, now)
+            })
+        }
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let dir = env::temp_dir().join(format!("overgg-archive-{}", ::std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let recorder = Recorder::new(Clock(RefCell::new(0)), Archive::open(&dir).unwrap());
+        recorder.fetch("https://www.over.gg/").unwrap();
+        recorder.fetch("https://www.over.gg/1/a-vs-b").unwrap();
+        let latest = recorder.fetch("https://www.over.gg/").unwrap();
+        drop(recorder);
+
+        // Reopening reads everything back from the index
+        let archive = Archive::open(&dir).unwrap();
+        assert_eq!(archive.len(), 3);
+        assert_eq!(archive.snapshots("https://www.over.gg/"), vec![
+            Utc.timestamp_opt(100, 0).unwrap(),
+            Utc.timestamp_opt(300, 0).unwrap()
+        ]);
+
+        assert_eq!(archive.replay().fetch("https://www.over.gg/").unwrap(), latest);
+        assert_eq!(
+            archive.replay().as_of(Utc.timestamp_opt(250, 0).unwrap()).fetch("https://www.over.gg/").unwrap().body,
+            "<p>https://www.over.gg/ at 100</p>"
+        );
+
+        match archive.replay().fetch("https://www.over.gg/2/c-vs-d") {
+            Err(Error::NotArchived(url)) => assert_eq!(url, "https://www.over.gg/2/c-vs-d"),
+            other => panic!("expected the page not to be archived, got {:?}", other)
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn existing_page_files_are_kept() {
+        let dir = env::temp_dir().join(format!("overgg-archive-files-{}", ::std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut archive = Archive::open(&dir).unwrap();
+        let clock = Clock(RefCell::new(0));
+        archive.save(&clock.fetch("https://www.over.gg/").unwrap()).unwrap();
+
+        // A page whose index entry never got written
+        fs::write(dir.join(PAGES_DIR).join("000002.html"), "orphan").unwrap();
+
+        let page = clock.fetch("https://www.over.gg/1/a-vs-b").unwrap();
+        archive.save(&page).unwrap();
+
+        assert_eq!(fs::read_to_string(dir.join(PAGES_DIR).join("000002.html")).unwrap(), "orphan");
+        assert_eq!(Archive::open(&dir).unwrap().replay().fetch("https://www.over.gg/1/a-vs-b").unwrap(), page);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,365 @@
+//! Command-line access to the data this crate can scrape.
+//!
+//! Run `overgg --help` for usage information.
+
+extern crate overgg_scraper;
+extern crate serde;
+extern crate serde_json;
+
+use overgg_scraper::data_structs::{EventId, MatchBriefType, MatchId, TeamId};
+use overgg_scraper::data_structs::MatchBriefType::*;
+use overgg_scraper::error::Error;
+use overgg_scraper::export::csv::{self, CsvRecord, LivestreamRecord, MapRecord};
+use overgg_scraper::http_client::Downloader;
+use overgg_scraper::scrapers::event_page::EventPageScraper;
+use overgg_scraper::scrapers::main_page::MainPageScraper;
+use overgg_scraper::scrapers::match_page::MatchPageScraper;
+use overgg_scraper::scrapers::team_page::TeamPageScraper;
+use overgg_scraper::site::Site;
+use serde::Serialize;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::process;
+
+const USAGE: &str = "\
+Query data from over.gg
+
+USAGE:
+    overgg [OPTIONS] <COMMAND>
+
+COMMANDS:
+    matches --completed|--upcoming|--live    Matches listed on the main page
+    streams                                  Livestreams listed on the main page
+    match <ID>                               The maps of the match with the given ID
+    team <ID>                                The matches on the page of the team with the given ID
+                                             (JSON also has the team's name and roster)
+    event <ID>                               The matches on the page of the event with the given ID
+                                             (JSON also has the event's name)
+
+OPTIONS:
+    --format <table|json|csv>    Output format [default: table]
+    --from-file <PATH>           Read the page HTML from a file instead of the site
+    --base-url <URL>             Where the site is (or where the file came from)
+                                 [default: https://www.over.gg/]
+    --strict                     Fail if any expected information is missing
+    -h, --help                   Print this message
+
+EXIT CODES:
+    0    Success
+    1    The page could not be fetched or the data could not be extracted
+    2    The command line arguments were invalid
+";
+
+fn main() {
+    let args = match Args::parse(env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{}", USAGE);
+            return;
+        },
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+
+    if let Err(e) = actual_main(args) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn actual_main(args: Args) -> Result<(), Error> {
+    let site = match args.base_url {
+        Some(ref base_url) => Site::new(base_url)?,
+        None => Site::default()
+    };
+
+    match args.command {
+        Command::Matches(_type) => {
+            let scraper = main_page(&args, site)?;
+            let matches = if args.strict {
+                scraper.matches_brief_strict(_type)?
+            } else {
+                scraper.matches_brief(_type)
+            };
+
+            output(&args.format, &matches, &matches)
+        },
+        Command::Streams => {
+            let scraper = main_page(&args, site)?;
+            let streams = if args.strict {
+                scraper.live_streams_strict()?
+            } else {
+                scraper.live_streams()
+            };
+
+            output(&args.format, &streams, &LivestreamRecord::from_livestreams(&streams))
+        },
+        Command::Match(id) => {
+            let scraper = match args.from_file {
+                Some(ref path) => MatchPageScraper::from(fs::read_to_string(path)?).with_site(site).with_id(id),
+                None => Downloader::new().with_site(site).match_page(id)?
+            };
+            let state = if args.strict {
+                scraper.live_state_strict()?
+            } else {
+                scraper.live_state()
+            };
+
+            output(&args.format, &state, &MapRecord::from_live_state(&state))
+        },
+        Command::Team(id) => {
+            let scraper = match args.from_file {
+                Some(ref path) => TeamPageScraper::from(fs::read_to_string(path)?).with_site(site).with_id(id),
+                None => Downloader::new().with_site(site).team_page(id)?
+            };
+            let info = if args.strict {
+                scraper.team_info_strict()?
+            } else {
+                scraper.team_info()
+            };
+
+            output(&args.format, &info, &info.matches)
+        },
+        Command::Event(id) => {
+            let scraper = match args.from_file {
+                Some(ref path) => EventPageScraper::from(fs::read_to_string(path)?).with_site(site).with_id(id),
+                None => Downloader::new().with_site(site).event_page(id)?
+            };
+            let info = if args.strict {
+                scraper.event_info_strict()?
+            } else {
+                scraper.event_info()
+            };
+
+            output(&args.format, &info, &info.matches)
+        }
+    }
+}
+
+fn main_page(args: &Args, site: Site) -> Result<MainPageScraper, Error> {
+    Ok(match args.from_file {
+        Some(ref path) => MainPageScraper::from(fs::read_to_string(path)?).with_site(site),
+        None => Downloader::new().with_site(site).main_page()?
+    })
+}
+
+/// Prints `data` in the given format, using `records` for the tabular formats.
+fn output<S: Serialize, R: CsvRecord>(format: &Format, data: &S, records: &[R]) -> Result<(), Error> {
+    match *format {
+        Format::Table => println!("{}", table(records)),
+        Format::Json => println!("{}", serde_json::to_string_pretty(data)?),
+        Format::Csv => csv::write(io::stdout().lock(), records)?
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Matches(MatchBriefType),
+    Streams,
+    Match(MatchId),
+    Team(TeamId),
+    Event(EventId)
+}
+
+#[derive(Debug, PartialEq)]
+enum Format {
+    Table,
+    Json,
+    Csv
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    command: Command,
+    format: Format,
+    from_file: Option<String>,
+    base_url: Option<String>,
+    strict: bool
+}
+
+/// An invalid command line.
+#[derive(Debug, PartialEq)]
+struct UsageError(String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Args {
+    /// Parses the given arguments (minus the program name).
+    ///
+    /// Returns `None` if help was requested.
+    fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Self>, UsageError> {
+        let mut command = None;
+        let mut match_type = None;
+        let mut id = None;
+        let mut format = Format::Table;
+        let mut from_file = None;
+        let mut base_url = None;
+        let mut strict = false;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--format" => format = match args.next().as_deref() {
+                    Some("table") => Format::Table,
+                    Some("json") => Format::Json,
+                    Some("csv") => Format::Csv,
+                    Some(other) => return Err(UsageError(format!("unknown format {:?}", other))),
+                    None => return Err(UsageError("--format requires a value".into()))
+                },
+                "--from-file" => match args.next() {
+                    Some(path) => from_file = Some(path),
+                    None => return Err(UsageError("--from-file requires a path".into()))
+                },
+                "--base-url" => match args.next() {
+                    Some(url) => base_url = Some(url),
+                    None => return Err(UsageError("--base-url requires a URL".into()))
+                },
+                "--strict" => strict = true,
+                "--completed" | "--upcoming" | "--live" => {
+                    if match_type.is_some() {
+                        return Err(UsageError("only one type of match can be given".into()));
+                    }
+
+                    match_type = Some(match arg.as_str() {
+                        "--completed" => Completed,
+                        "--upcoming" => InFuture,
+                        _ => Live
+                    });
+                },
+                "matches" | "streams" if command.is_none() => command = Some(arg),
+                "match" | "team" | "event" if command.is_none() => {
+                    id = match args.next() {
+                        Some(value) => match value.parse::<u32>() {
+                            Ok(value) => Some(value),
+                            Err(_) => return Err(UsageError(format!("invalid {} ID {:?}", arg, value)))
+                        },
+                        None => return Err(UsageError(format!("{} requires an ID", arg)))
+                    };
+                    command = Some(arg);
+                },
+                other => return Err(UsageError(format!("unexpected argument {:?}", other)))
+            }
+        }
+
+        let command = match (command.as_deref(), match_type) {
+            (Some("matches"), Some(_type)) => Command::Matches(_type),
+            (Some("matches"), None) => {
+                return Err(UsageError("matches requires one of --completed, --upcoming or --live".into()));
+            },
+            (Some("streams"), None) => Command::Streams,
+            // Matches, teams and events are always given along with their ID
+            (Some("match"), None) => Command::Match(MatchId(id.unwrap())),
+            (Some("team"), None) => Command::Team(TeamId(id.unwrap())),
+            (Some("event"), None) => Command::Event(EventId(id.unwrap())),
+            (Some(_), Some(_)) => {
+                return Err(UsageError("--completed, --upcoming and --live only apply to matches".into()));
+            },
+            (_, _) => return Err(UsageError("no command given".into()))
+        };
+
+        Ok(Some(Args {
+            command,
+            format,
+            from_file,
+            base_url,
+            strict
+        }))
+    }
+}
+
+/// Lays the given records out in aligned columns.
+fn table<R: CsvRecord>(records: &[R]) -> String {
+    let header: Vec<String> = R::HEADER.iter().map(|h| h.to_uppercase()).collect();
+    let rows: Vec<Vec<String>> = records.iter().map(CsvRecord::fields).collect();
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: &[String]| {
+        cells.iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:1$}", cell, width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![line(&header)];
+    lines.extend(rows.iter().map(|row| line(row)));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Args>, UsageError> {
+        Args::parse(args.iter().map(|&arg| String::from(arg)))
+    }
+
+    fn error(message: &str) -> Result<Option<Args>, UsageError> {
+        Err(UsageError(message.into()))
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(parse(&["matches", "--live"]), Ok(Some(Args {
+            command: Command::Matches(Live),
+            format: Format::Table,
+            from_file: None,
+            base_url: None,
+            strict: false
+        })));
+        assert_eq!(
+            parse(&["--format", "csv", "streams", "--strict", "--from-file", "page.html"]),
+            Ok(Some(Args {
+                command: Command::Streams,
+                format: Format::Csv,
+                from_file: Some("page.html".into()),
+                base_url: None,
+                strict: true
+            }))
+        );
+        assert_eq!(
+            parse(&["match", "12345", "--format", "json"]).unwrap().unwrap().command,
+            Command::Match(MatchId(12345))
+        );
+        assert_eq!(parse(&["team", "7"]).unwrap().unwrap().command, Command::Team(TeamId(7)));
+        assert_eq!(
+            parse(&["event", "42", "--from-file", "event.html"]).unwrap().unwrap().command,
+            Command::Event(EventId(42))
+        );
+        assert_eq!(parse(&["streams", "--help"]), Ok(None));
+    }
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        assert_eq!(parse(&[]), error("no command given"));
+        assert_eq!(parse(&["matches"]), error("matches requires one of --completed, --upcoming or --live"));
+        assert_eq!(parse(&["matches", "--live", "--completed"]), error("only one type of match can be given"));
+        assert_eq!(parse(&["streams", "--live"]), error("--completed, --upcoming and --live only apply to matches"));
+        assert_eq!(parse(&["match"]), error("match requires an ID"));
+        assert_eq!(parse(&["match", "twelve"]), error("invalid match ID \"twelve\""));
+        assert_eq!(parse(&["team"]), error("team requires an ID"));
+        assert_eq!(parse(&["event", "-1"]), error("invalid event ID \"-1\""));
+        assert_eq!(parse(&["team", "7", "--upcoming"]), error("--completed, --upcoming and --live only apply to matches"));
+        assert_eq!(parse(&["streams", "matches"]), error("unexpected argument \"matches\""));
+        assert_eq!(parse(&["streams", "--format", "xml"]), error("unknown format \"xml\""));
+        assert_eq!(parse(&["streams", "--from-file"]), error("--from-file requires a path"));
+    }
+}
@@ -0,0 +1,545 @@
+//! Crawls the site breadth-first, following links between match, team, event
+//! and player pages. A crawl can also walk the results archive page by page
+//! (see `Crawler::from_results_archive`).
+//!
+//! The crawler only discovers and fetches pages; what to do with each one
+//! (scraping, archiving, storing) is up to you. It can persist its progress to
+//! a file after every page so that a crashed crawl can pick up where it left
+//! off. A page only counts as done once you've asked for the next one, so the
+//! page you were handling when a crawl crashed is fetched again when it
+//! resumes.
+//!
+//! ```no_run
+//! # use overgg_scraper::crawler::Crawler;
+//! # use overgg_scraper::fetch::Fetcher;
+//! # fn crawl<F: Fetcher>(fetcher: F) -> overgg_scraper::error::Result<()> {
+//! // `fetcher` would usually be an `http_client::Downloader`
+//! let crawler = Crawler::from_main_page(fetcher)
+//!     .max_depth(2)
+//!     .max_pages(500)
+//!     .persist_to("crawl-state.toml")?;
+//!
+//! for page in crawler {
+//!     let page = page?;
+//!     println!("{} ({:?})", page.page.url, page.link);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::*;
+use crate::fetch::{Fetcher, Page};
+use crate::scrapers::links::{results_page_links, site_links, SiteLink};
+use crate::site::{self, Site};
+use std::collections::{BTreeSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use url::Url;
+
+/// Where `Crawler::from_main_page` starts.
+pub const MAIN_PAGE: &str = site::DEFAULT_BASE_URL;
+
+/// A page visited by the crawler.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CrawledPage {
+    pub page: Page,
+    /// What kind of page this is, if it's one with an ID.
+    pub link: Option<SiteLink>,
+    /// How many links away from a starting page this page is.
+    pub depth: u32
+}
+
+/// The progress of a crawl, which is what gets persisted.
+#[derive(Debug, Default, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+struct CrawlState {
+    pages_fetched: usize,
+    /// Keys of every page that has been queued, visited or not.
+    seen: BTreeSet<String>,
+    queue: VecDeque<QueuedPage>
+}
+
+#[derive(Debug, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+struct QueuedPage {
+    url: String,
+    depth: u32,
+    /// How many times fetching the page has failed.
+    failures: u32
+}
+
+/// The page that was last handed out, which is finished with once the next
+/// page is asked for.
+struct InFlight {
+    links: Vec<(Url, SiteLink)>,
+    /// Later pages of the results archive, which are queued at the same
+    /// depth as the page that links to them.
+    results_pages: Vec<Url>,
+    depth: u32
+}
+
+/// Crawls the site breadth-first.
+///
+/// Pages are deduplicated by ID (or by URL for pages without one), so each
+/// match, team, event and player is only fetched once no matter how many
+/// different URLs link to it.
+///
+/// This is an `Iterator` that fetches a page each time it's advanced. Pages
+/// that fail to fetch are yielded as errors and put back at the end of the
+/// queue, until they've failed more than `max_retries` times.
+pub struct Crawler<F> {
+    fetcher: F,
+    site: Site,
+    max_depth: u32,
+    max_pages: usize,
+    max_retries: u32,
+    delay: Duration,
+    state_path: Option<PathBuf>,
+    state: CrawlState,
+    in_flight: Option<InFlight>,
+    fetched_this_run: bool,
+    follow_results_pages: bool
+}
+
+impl<F: Fetcher> Crawler<F> {
+    /// Create a crawler that starts from the given URL.
+    ///
+    /// The site is taken to be served from the root of the URL's origin; use
+    /// `from_site` for a copy of the site served under a path.
+    ///
+    /// By default the crawler follows links up to 1 level deep, fetches at
+    /// most 100 pages, retries each page twice and waits 1 second between
+    /// requests.
+    pub fn new(fetcher: F, start_url: &str) -> Result<Self> {
+        let site = Site::new(Url::parse(start_url)?.join("/")?.as_str())?;
+        Self::with_site(fetcher, site, start_url)
+    }
+
+    /// Create a crawler that starts from the main page.
+    #[inline]
+    pub fn from_main_page(fetcher: F) -> Self {
+        Self::from_site(fetcher, &Site::default())
+    }
+
+    /// Create a crawler that starts from the main page of the given site.
+    #[inline]
+    pub fn from_site(fetcher: F, site: &Site) -> Self {
+        // The main page URL is known to be valid
+        Self::with_site(fetcher, site.clone(), site.main_page().as_str()).unwrap()
+    }
+
+    /// Create a crawler that starts from the given page of the results
+    /// archive of the given site (counting from 1), and goes on to the later
+    /// pages through the archive's pagination links.
+    ///
+    /// Every page of the archive counts as a starting page, so with the
+    /// default `max_depth` of 1 the crawler fetches the archive and the
+    /// matches listed on it, until it reaches `max_pages`.
+    pub fn from_results_archive(fetcher: F, site: &Site, page: u32) -> Self {
+        // Results page URLs are known to be valid
+        let crawler = Self::with_site(fetcher, site.clone(), site.results_page(page).as_str()).unwrap();
+
+        Self {
+            follow_results_pages: true,
+            ..crawler
+        }
+    }
+
+    fn with_site(fetcher: F, site: Site, start_url: &str) -> Result<Self> {
+        let start_url = Url::parse(start_url)?;
+        let mut state = CrawlState::default();
+        state.seen.insert(page_key(&site, &start_url));
+        state.queue.push_back(QueuedPage {
+            url: start_url.into(),
+            depth: 0,
+            failures: 0
+        });
+
+        Ok(Self {
+            fetcher,
+            site,
+            max_depth: 1,
+            max_pages: 100,
+            max_retries: 2,
+            delay: Duration::from_secs(1),
+            state_path: None,
+            state,
+            in_flight: None,
+            fetched_this_run: false,
+            follow_results_pages: false
+        })
+    }
+
+    /// The number of links away from a starting page to follow.
+    #[inline]
+    pub fn max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// The number of pages to fetch before stopping, including those fetched
+    /// before a resumed crawl was interrupted.
+    #[inline]
+    pub fn max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages;
+        self
+    }
+
+    /// The number of times to retry a page that fails to fetch.
+    ///
+    /// Failed attempts count towards `max_pages`.
+    #[inline]
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// How long to wait between requests.
+    ///
+    /// Please be considerate of the site and don't set this too low.
+    #[inline]
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Save the crawl's progress to the given file after every page.
+    ///
+    /// If the file already exists, the crawl it describes is resumed instead
+    /// of starting over.
+    pub fn persist_to<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
+        let path = path.as_ref();
+
+        if path.exists() {
+            self.state = ::toml::from_str(&fs::read_to_string(path)?)?;
+        }
+
+        self.state_path = Some(path.into());
+        self.save()?;
+        Ok(self)
+    }
+
+    /// The number of pages fetched so far.
+    #[inline]
+    pub fn pages_fetched(&self) -> usize {
+        self.state.pages_fetched
+    }
+
+    /// The number of pages waiting to be fetched.
+    #[inline]
+    pub fn pages_queued(&self) -> usize {
+        self.state.queue.len()
+    }
+
+    /// Fetches the page at the front of the queue, leaving it there until
+    /// it's finished with.
+    fn crawl_next(&mut self) -> Result<CrawledPage> {
+        if self.fetched_this_run {
+            thread::sleep(self.delay);
+        }
+
+        self.fetched_this_run = true;
+
+        // Only called with pages in the queue
+        let queued = &self.state.queue[0];
+        let url = Url::parse(&queued.url)?;
+        let page = self.fetcher.fetch(&queued.url)?;
+
+        let links = if queued.depth < self.max_depth {
+            site_links(&page.body, &self.site)
+        } else {
+            vec![]
+        };
+
+        let results_pages = match self.site.results_page_number(url.as_str()) {
+            Some(number) if self.follow_results_pages => results_page_links(&page.body, &self.site)
+                .into_iter()
+                .filter(|&(_, linked)| linked > number)
+                .map(|(linked_url, _)| linked_url)
+                .collect(),
+            _ => vec![]
+        };
+
+        self.in_flight = Some(InFlight {
+            links,
+            results_pages,
+            depth: queued.depth
+        });
+
+        Ok(CrawledPage {
+            link: self.site.link(url.as_str()),
+            depth: queued.depth,
+            page
+        })
+    }
+
+    /// Takes the page that was last handed out off the queue, and queues the
+    /// pages it links to.
+    fn finish_in_flight(&mut self) {
+        let in_flight = match self.in_flight.take() {
+            Some(in_flight) => in_flight,
+            None => return
+        };
+
+        self.state.queue.pop_front();
+        self.state.pages_fetched += 1;
+
+        for results_url in in_flight.results_pages {
+            if self.state.seen.insert(page_key(&self.site, &results_url)) {
+                self.state.queue.push_back(QueuedPage {
+                    url: results_url.into(),
+                    depth: in_flight.depth,
+                    failures: 0
+                });
+            }
+        }
+
+        for (link_url, link) in in_flight.links {
+            if self.state.seen.insert(link.key()) {
+                self.state.queue.push_back(QueuedPage {
+                    url: link_url.into(),
+                    depth: in_flight.depth + 1,
+                    failures: 0
+                });
+            }
+        }
+    }
+
+    /// Puts the page at the front of the queue at the back, or drops it if it
+    /// has failed too many times.
+    fn retry_later(&mut self) {
+        self.state.pages_fetched += 1;
+
+        if let Some(mut queued) = self.state.queue.pop_front() {
+            queued.failures += 1;
+
+            if queued.failures <= self.max_retries {
+                self.state.queue.push_back(queued);
+            }
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(ref path) = self.state_path {
+            // Write to the side and rename so a crash can't leave a half
+            // written file behind
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, ::toml::to_string(&self.state)?)?;
+            fs::rename(&tmp, path)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<F: Fetcher> Iterator for Crawler<F> {
+    type Item = Result<CrawledPage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Asking for the next page means the last one has been dealt with
+        if self.in_flight.is_some() {
+            self.finish_in_flight();
+
+            if let Err(e) = self.save() {
+                return Some(Err(e));
+            }
+        }
+
+        if self.state.pages_fetched >= self.max_pages || self.state.queue.is_empty() {
+            return None;
+        }
+
+        match self.crawl_next() {
+            Ok(crawled) => Some(Ok(crawled)),
+            Err(e) => {
+                self.retry_later();
+
+                match self.save() {
+                    Ok(()) => Some(Err(e)),
+                    Err(save_error) => Some(Err(save_error))
+                }
+            }
+        }
+    }
+}
+
+/// The key a page on the given site is deduplicated by.
+fn page_key(site: &Site, url: &Url) -> String {
+    match site.link(url.as_str()) {
+        Some(link) => link.key(),
+        None => url.as_str().into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data_structs::{MatchId, TeamId};
+    use chrono::Utc;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::env;
+
+    /// Serves pages from memory, failing the number of times given for each
+    /// URL in `failures` first.
+    struct MapFetcher(HashMap<&'static str, &'static str>, RefCell<HashMap<&'static str, u32>>);
+
+    impl Fetcher for MapFetcher {
+        fn fetch(&self, url: &str) -> Result<Page> {
+            if let Some(failures) = self.1.borrow_mut().get_mut(url).filter(|failures| **failures > 0) {
+                *failures -= 1;
+                return Err(::std::io::Error::new(::std::io::ErrorKind::TimedOut, url.to_string()).into());
+            }
+
+            match self.0.get(url) {
+                Some(body) => Ok(Page {
+                    url: url.into(),
+                    fetched_at: Utc::now(),
+                    headers: vec![],
+                    body: (*body).into()
+                }),
+                None => Err(::std::io::Error::new(::std::io::ErrorKind::NotFound, url.to_string()).into())
+            }
+        }
+    }
+
+    fn site() -> MapFetcher {
+        let mut pages = HashMap::new();
+        pages.insert(MAIN_PAGE, r#"<a href="/1/a-vs-b">1</a> <a href="/2/c-vs-d">2</a>"#);
+        pages.insert("https://www.over.gg/1/a-vs-b", r#"<a href="/team/7/a">a</a> <a href="/2/other-slug">2</a>"#);
+        pages.insert("https://www.over.gg/2/c-vs-d", r#"<a href="/team/7/a">a</a>"#);
+        pages.insert("https://www.over.gg/team/7/a", r#"<a href="/3/deeper">3</a>"#);
+        MapFetcher(pages, RefCell::default())
+    }
+
+    fn links<F: Fetcher>(crawler: Crawler<F>) -> Vec<Option<SiteLink>> {
+        crawler.map(|page| page.unwrap().link).collect()
+    }
+
+    #[test]
+    fn breadth_first_and_deduplicated() {
+        let crawler = Crawler::from_main_page(site()).max_depth(2).delay(Duration::from_secs(0));
+
+        assert_eq!(links(crawler), vec![
+            None,
+            Some(SiteLink::Match(MatchId(1))),
+            Some(SiteLink::Match(MatchId(2))),
+            Some(SiteLink::Team(TeamId(7)))
+        ]);
+    }
+
+    #[test]
+    fn limits() {
+        let shallow = Crawler::from_main_page(site()).delay(Duration::from_secs(0));
+        assert_eq!(links(shallow).len(), 3);
+
+        let few = Crawler::from_main_page(site()).max_depth(5).max_pages(2).delay(Duration::from_secs(0));
+        assert_eq!(links(few).len(), 2);
+    }
+
+    #[test]
+    fn crawls_mirrors_under_a_path() {
+        let mut pages = HashMap::new();
+        pages.insert("http://mirror.example/overgg/", r#"
+            <a href="/1/a-vs-b">1</a>
+            <a href="http://mirror.example/overgg/team/7/a">a</a>
+            <a href="http://mirror.example/2/outside-the-mirror">2</a>
+            <a href="https://www.over.gg/3/live-site">3</a>
+        "#);
+        pages.insert("http://mirror.example/overgg/1/a-vs-b", "");
+        pages.insert("http://mirror.example/overgg/team/7/a", "");
+        let fetcher = MapFetcher(pages, RefCell::default());
+
+        let mirror = Site::new("http://mirror.example/overgg").unwrap();
+        let crawler = Crawler::from_site(fetcher, &mirror).delay(Duration::from_secs(0));
+        assert_eq!(links(crawler), vec![
+            None,
+            Some(SiteLink::Match(MatchId(1))),
+            Some(SiteLink::Team(TeamId(7)))
+        ]);
+    }
+
+    #[test]
+    fn walks_the_results_archive() {
+        let mut pages = HashMap::new();
+        pages.insert("https://www.over.gg/matches/results?page=2", r#"
+            <a href="/1/a-vs-b">1</a>
+            <a href="/matches/results">Previous</a>
+            <a href="/matches/results?page=3">Next</a>
+        "#);
+        pages.insert("https://www.over.gg/matches/results?page=3", r#"
+            <a href="/2/c-vs-d">2</a>
+            <a href="/matches/results?page=2">Previous</a>
+        "#);
+        pages.insert("https://www.over.gg/1/a-vs-b", r#"<a href="/matches/results?page=4">results</a>"#);
+        pages.insert("https://www.over.gg/2/c-vs-d", "");
+        let fetcher = MapFetcher(pages, RefCell::default());
+
+        let crawler = Crawler::from_results_archive(&fetcher, &Site::default(), 2).delay(Duration::from_secs(0));
+        let urls: Vec<String> = crawler.map(|page| page.unwrap().page.url).collect();
+        assert_eq!(urls, vec![
+            "https://www.over.gg/matches/results?page=2",
+            "https://www.over.gg/matches/results?page=3",
+            "https://www.over.gg/1/a-vs-b",
+            "https://www.over.gg/2/c-vs-d"
+        ]);
+
+        // Other crawls don't follow the archive
+        let crawler = Crawler::new(&fetcher, "https://www.over.gg/matches/results?page=2").unwrap()
+            .delay(Duration::from_secs(0));
+        assert_eq!(crawler.count(), 2);
+    }
+
+    #[test]
+    fn resumes_from_persisted_state() {
+        let path = env::temp_dir().join(format!("overgg-crawl-{}.toml", ::std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut first = Crawler::from_main_page(site())
+            .max_depth(2)
+            .delay(Duration::from_secs(0))
+            .persist_to(&path)
+            .unwrap();
+        first.next().unwrap().unwrap();
+        first.next().unwrap().unwrap();
+        // Simulate a crash while the second page is being handled
+        drop(first);
+
+        let resumed = Crawler::from_main_page(site())
+            .max_depth(2)
+            .delay(Duration::from_secs(0))
+            .persist_to(&path)
+            .unwrap();
+        assert_eq!(resumed.pages_fetched(), 1);
+        assert_eq!(links(resumed), vec![
+            Some(SiteLink::Match(MatchId(1))),
+            Some(SiteLink::Match(MatchId(2))),
+            Some(SiteLink::Team(TeamId(7)))
+        ]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn retries_failed_pages() {
+        let fetcher = site();
+        fetcher.1.borrow_mut().insert("https://www.over.gg/1/a-vs-b", 1);
+        fetcher.1.borrow_mut().insert("https://www.over.gg/2/c-vs-d", 5);
+
+        let crawler = Crawler::from_main_page(fetcher).max_retries(2).delay(Duration::from_secs(0));
+        let results: Vec<::std::result::Result<Option<SiteLink>, ()>> = crawler
+            .map(|page| page.map(|page| page.link).map_err(|_| ()))
+            .collect();
+
+        // Failed pages go to the back of the queue, and are given up on after
+        // failing three times
+        assert_eq!(results, vec![
+            Ok(None),
+            Err(()),
+            Err(()),
+            Ok(Some(SiteLink::Match(MatchId(1)))),
+            Err(()),
+            Err(())
+        ]);
+    }
+}
@@ -1,6 +1,9 @@
 #![allow(unused)]
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeZone, Utc};
+use std::borrow::Cow;
+use std::fmt;
+use url::Url;
 use self::MatchBriefType::InFuture as InFutureType;
 use self::MatchBriefType::Live as LiveType;
 use self::MatchBriefType::Completed as CompletedType;
@@ -30,22 +33,122 @@ impl From<MatchBriefType> for String {
 }
 
 /// Used to statically type the two teams in a match.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
 #[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
 pub enum Team {
     Zero,
     One
 }
 
+/// The number the site identifies a match by (the `12345` in
+/// `https://www.over.gg/12345/...`).
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub struct MatchId(pub u32);
+
+/// The number the site identifies a team by (the `123` in
+/// `https://www.over.gg/team/123/...`).
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub struct TeamId(pub u32);
+
+/// The number the site identifies an event by (the `123` in
+/// `https://www.over.gg/event/123/...`).
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub struct EventId(pub u32);
+
+/// The number the site identifies a player by (the `123` in
+/// `https://www.over.gg/player/123/...`).
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub struct PlayerId(pub u32);
+
+macro_rules! display_id {
+    ($($id:ident),*) => {$(
+        impl fmt::Display for $id {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    )*}
+}
+
+display_id!(MatchId, TeamId, EventId, PlayerId);
+
 #[derive(Debug, Default, PartialEq, Clone)]
 #[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
 pub struct MatchBriefInfo {
+    pub id: Option<MatchId>,
     pub event: EventInfo,
     pub teams: [TeamCompletedMatchBriefInfo; 2],
-    pub scheduled_time: Option<DateTime<Utc>>
+    pub scheduled_time: Option<DateTime<Utc>>,
+    /// Where `scheduled_time` came from, if there is one, or `Live` for a
+    /// match that was live without a time.
+    pub time_source: Option<TimeSource>
+}
+
+/// Where a scraped time came from, and so how much it can be trusted.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub enum TimeSource {
+    /// The exact timestamp given by the site
+    Timestamp,
+    /// Worked out from countdown text (e.g. `2h 30m`) relative to when the
+    /// page was fetched, so only as precise as the countdown
+    Countdown,
+    /// The match was already live when the page was fetched. The site
+    /// doesn't say when it started, so there's no time
+    Live
 }
 
 impl MatchBriefInfo {
+    /// A key for this match that stays the same across scrapes, for use in
+    /// identifiers that outlive a single scrape (calendar UIDs, feed entry IDs,
+    /// database keys).
+    ///
+    /// This is the match's ID when it has one, and otherwise a hash of its
+    /// event and team names (which stays stable as long as the site doesn't
+    /// rename anything).
+    pub fn stable_key(&self) -> String {
+        match self.id {
+            Some(id) => id.to_string(),
+            None => self.name_key()
+        }
+    }
+
+    /// The hash of the match's event and team names used by `stable_key` for
+    /// matches without an ID.
+    pub(crate) fn name_key(&self) -> String {
+        let identity = [
+            &self.event.name[..],
+            &self.event.series,
+            &self.teams[0].name(),
+            &self.teams[1].name()
+        ];
+
+        format!("{:016x}", fnv1a(identity.iter().flat_map(|s| s.bytes().chain(Some(0)))))
+    }
+
+    /// `scheduled_time` in the given time zone.
+    ///
+    /// Works with any `chrono` time zone, including `chrono_tz::Tz`; see
+    /// `scheduled_time_in_zone` for looking zones up by name.
+    #[inline]
+    pub fn scheduled_time_in<Tz: TimeZone>(&self, tz: &Tz) -> Option<DateTime<Tz>> {
+        self.scheduled_time.map(|time| time.with_timezone(tz))
+    }
+
+    /// `scheduled_time` in the IANA time zone with the given name (e.g.
+    /// `Europe/Berlin`).
+    ///
+    /// Requires the `timezones` feature.
+    #[cfg(feature = "timezones")]
+    pub fn scheduled_time_in_zone(&self, name: &str) -> crate::error::Result<Option<DateTime<::chrono_tz::Tz>>> {
+        let tz: ::chrono_tz::Tz = name.parse().map_err(|_| crate::error::Error::UnknownTimeZone(name.into()))?;
+        Ok(self.scheduled_time_in(&tz))
+    }
+
     /// Determines which team won the match.
     ///
     /// Will be `None` if neither team won (match was a draw).
@@ -75,11 +178,115 @@ impl MatchBriefInfo {
     }
 }
 
+/// The 64-bit FNV-1a hash of the given bytes.
+///
+/// Used instead of `std`'s hashers because its output has to be the same
+/// across Rust versions.
+fn fnv1a<I: IntoIterator<Item = u8>>(bytes: I) -> u64 {
+    bytes.into_iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
 #[derive(Default, Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
 pub struct TeamCompletedMatchBriefInfo {
-    pub name: String,
-    pub maps_won: Option<u8>
+    pub maps_won: Option<u8>,
+    /// Who fills this spot in the match.
+    pub slot: TeamSlot
+}
+
+impl TeamCompletedMatchBriefInfo {
+    /// The name to show for the team, which is a placeholder such as `TBD`
+    /// or `Winner of #12345` when the team isn't known yet.
+    #[inline]
+    pub fn name(&self) -> Cow<'_, str> {
+        match self.slot {
+            TeamSlot::Known(ref name) | TeamSlot::Placeholder(ref name) => Cow::Borrowed(name),
+            ref slot => Cow::Owned(slot.to_string())
+        }
+    }
+}
+
+/// Names the site shows for a team that isn't known yet, in lowercase.
+pub(crate) const PLACEHOLDER_NAMES: &[&str] = &["", "tbd", "tba", "to be determined", "to be announced", "?"];
+
+/// Who plays in one of the two spots in a match.
+///
+/// Upcoming matches in a bracket often don't have both teams decided yet, in
+/// which case the page may say where the team will come from.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub enum TeamSlot {
+    /// The team with the given name
+    Known(String),
+    /// Not decided yet, and the page doesn't say how it will be
+    Tbd,
+    /// Not decided yet, and the page says how it will be with the given
+    /// text (e.g. `Winner of Semifinal 2`) but not in a way that can be
+    /// followed to a match
+    Placeholder(String),
+    /// Whoever wins the match with the given ID
+    WinnerOf(MatchId),
+    /// Whoever loses the match with the given ID
+    LoserOf(MatchId)
+}
+
+impl Default for TeamSlot {
+    #[inline]
+    fn default() -> Self {
+        TeamSlot::Tbd
+    }
+}
+
+impl TeamSlot {
+    /// The slot for a team shown with the given name, which is `Tbd` for
+    /// placeholder names such as `TBD` and `Known` otherwise.
+    pub fn from_name(name: &str) -> Self {
+        let name = name.trim();
+
+        if PLACEHOLDER_NAMES.contains(&name.to_lowercase().as_str()) {
+            TeamSlot::Tbd
+        } else {
+            TeamSlot::Known(name.into())
+        }
+    }
+
+    /// Whether the team in this slot is known.
+    #[inline]
+    pub fn is_known(&self) -> bool {
+        matches!(*self, TeamSlot::Known(_))
+    }
+
+    /// The name of the team in this slot, if it's known.
+    #[inline]
+    pub fn known_name(&self) -> Option<&str> {
+        match *self {
+            TeamSlot::Known(ref name) => Some(name),
+            _ => None
+        }
+    }
+
+    /// The match this slot depends on the result of, if any.
+    #[inline]
+    pub fn depends_on(&self) -> Option<MatchId> {
+        match *self {
+            TeamSlot::WinnerOf(id) | TeamSlot::LoserOf(id) => Some(id),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for TeamSlot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TeamSlot::Known(ref name) => f.write_str(name),
+            TeamSlot::Tbd => f.write_str("TBD"),
+            TeamSlot::Placeholder(ref text) => f.write_str(text),
+            TeamSlot::WinnerOf(id) => write!(f, "Winner of #{}", id),
+            TeamSlot::LoserOf(id) => write!(f, "Loser of #{}", id)
+        }
+    }
 }
 
 #[derive(Default, Debug, PartialEq, Clone)]
@@ -89,6 +296,113 @@ pub struct EventInfo {
     pub series: String
 }
 
+/// The state of a match as shown on its page, including the score of the map
+/// being played if it's live.
+#[derive(Default, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub struct LiveMatchState {
+    /// The match's ID, if it's known where the page came from (see
+    /// `MatchPageScraper::with_id`).
+    pub id: Option<MatchId>,
+    pub event: EventInfo,
+    pub teams: [TeamCompletedMatchBriefInfo; 2],
+    pub scheduled_time: Option<DateTime<Utc>>,
+    /// Whether the site says the match is live.
+    pub is_live: bool,
+    /// Every map in the series in the order they're played, including ones
+    /// that haven't started yet.
+    pub maps: Vec<MapInfo>
+}
+
+impl LiveMatchState {
+    /// The map that's being played right now.
+    #[inline]
+    pub fn current_map(&self) -> Option<&MapInfo> {
+        self.maps.iter().find(|map| map.status == MapStatus::InProgress)
+    }
+
+    /// The maps that have already been played.
+    #[inline]
+    pub fn completed_maps(&self) -> impl Iterator<Item = &MapInfo> {
+        self.maps.iter().filter(|map| map.status == MapStatus::Completed)
+    }
+}
+
+/// What a team's page shows.
+#[derive(Default, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub struct TeamPageInfo {
+    /// The team's ID, if it's known where the page came from (see
+    /// `TeamPageScraper::with_id`).
+    pub id: Option<TeamId>,
+    pub name: String,
+    pub roster: Vec<PlayerBriefInfo>,
+    /// The team's recent and upcoming matches, as listed on the page.
+    pub matches: Vec<MatchBriefInfo>
+}
+
+/// A player as listed on another page, such as a team's roster.
+#[derive(Default, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub struct PlayerBriefInfo {
+    pub id: Option<PlayerId>,
+    pub name: String
+}
+
+/// What an event's page shows.
+#[derive(Default, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub struct EventPageInfo {
+    /// The event's ID, if it's known where the page came from (see
+    /// `EventPageScraper::with_id`).
+    pub id: Option<EventId>,
+    pub name: String,
+    /// The event's matches, as listed on the page.
+    pub matches: Vec<MatchBriefInfo>
+}
+
+/// A map in a series.
+#[derive(Default, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub struct MapInfo {
+    /// The map's name as shown on the site (`TBD` if it hasn't been picked).
+    pub name: String,
+    /// Each team's score on the map, in the same order as the match's teams.
+    pub scores: [Option<u8>; 2],
+    pub status: MapStatus
+}
+
+impl MapInfo {
+    /// The team that won the map, if it's been played and wasn't a draw.
+    pub fn winner(&self) -> Option<Team> {
+        if self.status != MapStatus::Completed {
+            return None;
+        }
+
+        match (self.scores[0], self.scores[1]) {
+            (Some(a), Some(b)) if a > b => Some(Zero),
+            (Some(a), Some(b)) if a < b => Some(One),
+            _ => None
+        }
+    }
+}
+
+/// How far along a map is.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub enum MapStatus {
+    Upcoming,
+    InProgress,
+    Completed
+}
+
+impl Default for MapStatus {
+    #[inline]
+    fn default() -> Self {
+        MapStatus::Upcoming
+    }
+}
+
 #[derive(Default, Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
 pub struct Livestreams {
@@ -102,8 +416,57 @@ pub struct LivestreamInfo {
     pub name: String,
     pub title: Option<String>,
     pub viewer_count: Option<u32>,
-    // TODO: Maybe this should be a `reqwest::Url` when `http_client` is on
-    pub url: String
+    /// Where to watch the stream, with any redirect through the site
+    /// unwrapped.
+    pub url: Option<Url>
+}
+
+impl LivestreamInfo {
+    /// Where the stream is hosted.
+    #[inline]
+    pub fn platform(&self) -> Option<StreamPlatform> {
+        self.url.as_ref().map(StreamPlatform::from_url)
+    }
+}
+
+/// A site that hosts livestreams.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub enum StreamPlatform {
+    Twitch,
+    YouTube,
+    Facebook,
+    /// Anywhere else, such as an event's own site
+    Custom
+}
+
+impl StreamPlatform {
+    /// Works out which platform the given stream URL is on.
+    pub fn from_url(url: &Url) -> Self {
+        let host = url.host_str().unwrap_or("").to_lowercase();
+        let on = |domain: &str| host == domain || host.ends_with(&format!(".{}", domain));
+
+        if on("twitch.tv") {
+            StreamPlatform::Twitch
+        } else if on("youtube.com") || on("youtu.be") {
+            StreamPlatform::YouTube
+        } else if on("facebook.com") || on("fb.gg") || on("fb.watch") {
+            StreamPlatform::Facebook
+        } else {
+            StreamPlatform::Custom
+        }
+    }
+}
+
+impl fmt::Display for StreamPlatform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            StreamPlatform::Twitch => "Twitch",
+            StreamPlatform::YouTube => "YouTube",
+            StreamPlatform::Facebook => "Facebook",
+            StreamPlatform::Custom => "Custom"
+        })
+    }
 }
 
 // TODO: Finish the beneath draft
@@ -124,3 +487,39 @@ pub struct TeamCompletedMatchInfo {
     pub maps_won: u8,
     // TODO: Map info
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn scheduled_time_in_time_zones() {
+        let _match = MatchBriefInfo {
+            scheduled_time: Some(Utc.timestamp_opt(1_533_600_000, 0).unwrap()),
+            ..MatchBriefInfo::default()
+        };
+
+        let seoul = FixedOffset::east_opt(9 * 60 * 60).unwrap();
+        assert_eq!(_match.scheduled_time_in(&seoul).unwrap().to_rfc3339(), "2018-08-07T09:00:00+09:00");
+        assert_eq!(MatchBriefInfo::default().scheduled_time_in(&seoul), None);
+    }
+
+    #[test]
+    #[cfg(feature = "timezones")]
+    fn scheduled_time_in_named_zones() {
+        let _match = MatchBriefInfo {
+            scheduled_time: Some(Utc.timestamp_opt(1_533_600_000, 0).unwrap()),
+            ..MatchBriefInfo::default()
+        };
+
+        // Daylight saving time is in effect in August
+        let berlin = _match.scheduled_time_in_zone("Europe/Berlin").unwrap().unwrap();
+        assert_eq!(berlin.to_rfc3339(), "2018-08-07T02:00:00+02:00");
+
+        match _match.scheduled_time_in_zone("Mars/Olympus_Mons") {
+            Err(crate::error::Error::UnknownTimeZone(name)) => assert_eq!(name, "Mars/Olympus_Mons"),
+            other => panic!("expected an unknown time zone error, got {:?}", other)
+        }
+    }
+}
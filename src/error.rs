@@ -1,24 +1,158 @@
+//! The error type used throughout this crate.
+
+use crate::scrapers::FieldIssue;
+#[cfg(feature = "http-client")]
 use reqwest::StatusCode;
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
 
-error_chain! {
-    foreign_links {
-        Json(::serde_json::Error)
-        #[cfg(test)]
-        #[cfg(feature = "test-local-data")];
-        Io(::std::io::Error);
-        Req(::reqwest::Error);
-    }
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+/// Everything that can go wrong while using this crate.
+///
+/// Some variants only exist when the feature they relate to is enabled.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    Io(io::Error),
+    Url(::url::ParseError),
+    TomlDe(::toml::de::Error),
+    TomlSer(::toml::ser::Error),
+    #[cfg(feature = "serde_json")]
+    Json(::serde_json::Error),
+    #[cfg(feature = "storage")]
+    Sqlite(::rusqlite::Error),
+    #[cfg(feature = "http-client")]
+    Req(::reqwest::Error),
+    /// The status code of a received response was not success.
+    #[cfg(feature = "http-client")]
+    NonSuccessStatus(StatusCode),
+    /// Something went wrong while attempting to extract information from HTML.
+    Extraction(FieldIssue),
+    /// The page at the given URL was requested from an `archive::Archive`
+    /// that doesn't have it.
+    NotArchived(String),
+    /// The given URL can't be the base URL of a `site::Site`, as links
+    /// can't be relative to it.
+    InvalidBaseUrl(String),
+    /// A `teams::TeamResolver` alias table is well-formed TOML, but what it
+    /// says doesn't make sense, for the given reason.
+    InvalidAliasTable(String),
+    /// There's no IANA time zone with the given name.
+    #[cfg(feature = "timezones")]
+    UnknownTimeZone(String)
+}
 
-    errors {
-        NonSuccessStatus(code: StatusCode) {
-            description("The status code of a received response was not success.")
-            display("The status code of a received response was {} and not success.",
-                     code)
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref e) => write!(f, "I/O error: {}", e),
+            Error::Url(ref e) => write!(f, "URL error: {}", e),
+            Error::TomlDe(ref e) => write!(f, "TOML deserialization error: {}", e),
+            Error::TomlSer(ref e) => write!(f, "TOML serialization error: {}", e),
+            #[cfg(feature = "serde_json")]
+            Error::Json(ref e) => write!(f, "JSON error: {}", e),
+            #[cfg(feature = "storage")]
+            Error::Sqlite(ref e) => write!(f, "SQLite error: {}", e),
+            #[cfg(feature = "http-client")]
+            Error::Req(ref e) => write!(f, "HTTP request error: {}", e),
+            #[cfg(feature = "http-client")]
+            Error::NonSuccessStatus(code) => {
+                write!(f, "The status code of a received response was {} and not success.", code)
+            },
+            Error::Extraction(ref issue) => {
+                write!(f, "Failed to extract information from HTML: {}", issue)
+            },
+            Error::NotArchived(ref url) => write!(f, "{} is not in the archive", url),
+            Error::InvalidBaseUrl(ref url) => write!(f, "{} can't be a base URL", url),
+            Error::InvalidAliasTable(ref reason) => write!(f, "Invalid alias table: {}", reason),
+            #[cfg(feature = "timezones")]
+            Error::UnknownTimeZone(ref name) => write!(f, "Unknown time zone {:?}", name)
         }
+    }
+}
 
-        ExtractionError {
-            description("Something went wrong while attempting to extract \
-                         information from HTML.")
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            Error::Io(ref e) => Some(e),
+            Error::Url(ref e) => Some(e),
+            Error::TomlDe(ref e) => Some(e),
+            Error::TomlSer(ref e) => Some(e),
+            #[cfg(feature = "serde_json")]
+            Error::Json(ref e) => Some(e),
+            #[cfg(feature = "storage")]
+            Error::Sqlite(ref e) => Some(e),
+            #[cfg(feature = "http-client")]
+            Error::Req(ref e) => Some(e),
+            #[cfg(feature = "http-client")]
+            Error::NonSuccessStatus(_) => None,
+            Error::Extraction(_) => None,
+            Error::NotArchived(_) => None,
+            Error::InvalidBaseUrl(_) => None,
+            Error::InvalidAliasTable(_) => None,
+            #[cfg(feature = "timezones")]
+            Error::UnknownTimeZone(_) => None
         }
     }
 }
+
+impl From<io::Error> for Error {
+    #[inline]
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<::url::ParseError> for Error {
+    #[inline]
+    fn from(e: ::url::ParseError) -> Self {
+        Error::Url(e)
+    }
+}
+
+impl From<::toml::de::Error> for Error {
+    #[inline]
+    fn from(e: ::toml::de::Error) -> Self {
+        Error::TomlDe(e)
+    }
+}
+
+impl From<::toml::ser::Error> for Error {
+    #[inline]
+    fn from(e: ::toml::ser::Error) -> Self {
+        Error::TomlSer(e)
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl From<::serde_json::Error> for Error {
+    #[inline]
+    fn from(e: ::serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+#[cfg(feature = "storage")]
+impl From<::rusqlite::Error> for Error {
+    #[inline]
+    fn from(e: ::rusqlite::Error) -> Self {
+        Error::Sqlite(e)
+    }
+}
+
+#[cfg(feature = "http-client")]
+impl From<::reqwest::Error> for Error {
+    #[inline]
+    fn from(e: ::reqwest::Error) -> Self {
+        Error::Req(e)
+    }
+}
+
+impl From<FieldIssue> for Error {
+    #[inline]
+    fn from(issue: FieldIssue) -> Self {
+        Error::Extraction(issue)
+    }
+}
@@ -0,0 +1,224 @@
+//! Writes scraped data as Atom ([RFC 4287]) feeds.
+//!
+//! `Feed` and `Entry` can hold any kind of item; `match_results` fills them
+//! in for completed matches. Entry IDs are derived from what an entry is
+//! about rather than when the feed was generated, so feed readers don't show
+//! duplicates when a feed is regenerated from a fresh scrape.
+//!
+//! [RFC 4287]: https://tools.ietf.org/html/rfc4287
+
+use crate::data_structs::MatchBriefInfo;
+use crate::error::*;
+use crate::site::Site;
+use chrono::{DateTime, Utc};
+use std::io::Write;
+
+/// An Atom feed.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Feed {
+    /// A permanent, unique identifier for the feed.
+    pub id: String,
+    pub title: String,
+    /// The page the feed's content comes from.
+    pub link: Option<String>,
+    pub updated: DateTime<Utc>,
+    pub entries: Vec<Entry>
+}
+
+/// An entry in an Atom feed.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Entry {
+    /// A permanent, unique identifier for the entry.
+    pub id: String,
+    pub title: String,
+    pub link: Option<String>,
+    pub summary: Option<String>,
+    pub updated: DateTime<Utc>
+}
+
+impl Feed {
+    /// Writes the feed as XML.
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        writeln!(writer, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+        writeln!(writer, r#"<feed xmlns="http://www.w3.org/2005/Atom">"#)?;
+        writeln!(writer, "  <id>{}</id>", escape(&self.id))?;
+        writeln!(writer, "  <title>{}</title>", escape(&self.title))?;
+
+        if let Some(ref link) = self.link {
+            writeln!(writer, r#"  <link href="{}"/>"#, escape(link))?;
+        }
+
+        writeln!(writer, "  <updated>{}</updated>", self.updated.to_rfc3339())?;
+        writeln!(writer, "  <author><name>over.gg</name></author>")?;
+
+        for entry in &self.entries {
+            writeln!(writer, "  <entry>")?;
+            writeln!(writer, "    <id>{}</id>", escape(&entry.id))?;
+            writeln!(writer, "    <title>{}</title>", escape(&entry.title))?;
+
+            if let Some(ref link) = entry.link {
+                writeln!(writer, r#"    <link href="{}"/>"#, escape(link))?;
+            }
+
+            if let Some(ref summary) = entry.summary {
+                writeln!(writer, "    <summary>{}</summary>", escape(summary))?;
+            }
+
+            writeln!(writer, "    <updated>{}</updated>", entry.updated.to_rfc3339())?;
+            writeln!(writer, "  </entry>")?;
+        }
+
+        writeln!(writer, "</feed>")?;
+        Ok(())
+    }
+
+    /// Like `write`, but gives you the feed as a `String`.
+    pub fn to_xml(&self) -> String {
+        let mut buf = vec![];
+        // Writing to a `Vec` can't fail
+        self.write(&mut buf).unwrap();
+        // Everything we write started out as valid UTF-8
+        String::from_utf8(buf).unwrap()
+    }
+}
+
+/// Builds a feed of the results of the given completed matches.
+///
+/// Entries are titled with the result (e.g. `Seoul Dynasty beat London
+/// Spitfire 3-1`) and dated by the match's `scheduled_time`. Matches without
+/// a scheduled time or without a score for both teams are left out, as they
+/// can't be given an entry that stays the same each time the feed is
+/// generated. `generated_at` is only used as the date of a feed without any
+/// entries.
+pub fn match_results(matches: &[MatchBriefInfo], generated_at: DateTime<Utc>) -> Feed {
+    // Always link to the live site, whichever copy the matches were scraped
+    // from
+    let site = Site::default();
+
+    let entries: Vec<Entry> = matches.iter().filter_map(|_match| {
+        let updated = _match.scheduled_time?;
+        let title = result_title(_match)?;
+        let summary = match (_match.event.name.is_empty(), _match.event.series.is_empty()) {
+            (false, false) => Some(format!("{}: {}", _match.event.name, _match.event.series)),
+            (false, true) => Some(_match.event.name.clone()),
+            (true, false) => Some(_match.event.series.clone()),
+            (true, true) => None
+        };
+
+        Some(Entry {
+            id: format!("tag:over.gg,2018:match/{}", _match.stable_key()),
+            title,
+            link: _match.id.map(|id| site.match_page(id).into()),
+            summary,
+            updated
+        })
+    }).collect();
+
+    Feed {
+        id: "tag:over.gg,2018:match-results".into(),
+        title: "over.gg match results".into(),
+        link: Some(site.main_page().into()),
+        updated: entries.iter().map(|e| e.updated).max().unwrap_or(generated_at),
+        entries
+    }
+}
+
+/// The result of the match, if both teams have a score.
+fn result_title(_match: &MatchBriefInfo) -> Option<String> {
+    let scores = (_match.teams[0].maps_won?, _match.teams[1].maps_won?);
+
+    Some(match (_match.winner(), _match.loser()) {
+        (Some(winner), Some(loser)) => format!(
+            "{} beat {} {}-{}",
+            winner.name(),
+            loser.name(),
+            scores.0.max(scores.1),
+            scores.0.min(scores.1)
+        ),
+        _ => format!(
+            "{} drew with {} {}-{}",
+            _match.teams[0].name(),
+            _match.teams[1].name(),
+            scores.0,
+            scores.1
+        )
+    })
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data_structs::{MatchId, TeamSlot};
+    use chrono::TimeZone;
+
+    fn completed(id: Option<MatchId>, scores: [u8; 2]) -> MatchBriefInfo {
+        let mut _match = MatchBriefInfo {
+            id,
+            scheduled_time: Some(Utc.timestamp_opt(1_533_600_000, 0).unwrap()),
+            ..MatchBriefInfo::default()
+        };
+        _match.event.name = "Overwatch League".into();
+        _match.event.series = "Stage 1".into();
+        _match.teams[0].slot = TeamSlot::Known("Seoul Dynasty".into());
+        _match.teams[0].maps_won = Some(scores[0]);
+        _match.teams[1].slot = TeamSlot::Known("London Spitfire".into());
+        _match.teams[1].maps_won = Some(scores[1]);
+        _match
+    }
+
+    #[test]
+    fn match_results_feed() {
+        let generated_at = Utc.timestamp_opt(1_533_700_000, 0).unwrap();
+        let feed = match_results(&[completed(Some(MatchId(12345)), [1, 3])], generated_at);
+
+        assert_eq!(feed.to_xml(), r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <id>tag:over.gg,2018:match-results</id>
+  <title>over.gg match results</title>
+  <link href="https://www.over.gg/"/>
+  <updated>2018-08-07T00:00:00+00:00</updated>
+  <author><name>over.gg</name></author>
+  <entry>
+    <id>tag:over.gg,2018:match/12345</id>
+    <title>London Spitfire beat Seoul Dynasty 3-1</title>
+    <link href="https://www.over.gg/12345"/>
+    <summary>Overwatch League: Stage 1</summary>
+    <updated>2018-08-07T00:00:00+00:00</updated>
+  </entry>
+</feed>
+"#);
+    }
+
+    #[test]
+    fn entry_ids_are_stable() {
+        let first = match_results(&[completed(None, [2, 2])], Utc::now());
+        let second = match_results(&[completed(None, [2, 2])], Utc::now());
+
+        assert_eq!(first.entries[0].id, second.entries[0].id);
+        assert_eq!(first.entries[0].title, "Seoul Dynasty drew with London Spitfire 2-2");
+    }
+
+    #[test]
+    fn leaves_out_unfinished_matches() {
+        let generated_at = Utc.timestamp_opt(1_533_700_000, 0).unwrap();
+        let mut untimed = completed(Some(MatchId(1)), [3, 0]);
+        untimed.scheduled_time = None;
+        let mut unscored = completed(Some(MatchId(2)), [3, 0]);
+        unscored.teams[1].maps_won = None;
+
+        let feed = match_results(&[untimed, unscored, completed(Some(MatchId(3)), [0, 3])], generated_at);
+        assert_eq!(feed.entries.len(), 1);
+        assert_eq!(feed.entries[0].link, Some("https://www.over.gg/3".into()));
+
+        let empty = match_results(&[], generated_at);
+        assert_eq!(empty.updated, generated_at);
+    }
+}
@@ -0,0 +1,284 @@
+//! Writes scraped data as CSV with stable column layouts and a header row.
+//!
+//! Fields are quoted only when they contain a comma, quote or line break, and
+//! missing values are written as empty fields. Times are written in RFC 3339
+//! format.
+
+use crate::data_structs::{MatchBriefInfo, Livestreams, LivestreamInfo, LiveMatchState, MapInfo, MapStatus};
+use crate::error::*;
+use std::io::Write;
+
+/// A value that can be written as a single CSV row.
+///
+/// Implement this for your own types to make use of `write` and `to_string`.
+pub trait CsvRecord {
+    /// The column names, written as the header row.
+    const HEADER: &'static [&'static str];
+
+    /// The value's fields, in the same order as `HEADER`.
+    fn fields(&self) -> Vec<String>;
+}
+
+/// Columns:
+///
+/// | Column | Contents |
+/// |--------|----------|
+/// | `event_name` | `event.name` |
+/// | `event_series` | `event.series` |
+/// | `team1_name` | `teams[0].name()` |
+/// | `team1_maps_won` | `teams[0].maps_won` |
+/// | `team2_name` | `teams[1].name()` |
+/// | `team2_maps_won` | `teams[1].maps_won` |
+/// | `scheduled_time` | `scheduled_time` |
+/// | `winner` | The name of the team returned by `winner()` |
+/// | `match_id` | `id` |
+impl CsvRecord for MatchBriefInfo {
+    const HEADER: &'static [&'static str] = &[
+        "event_name",
+        "event_series",
+        "team1_name",
+        "team1_maps_won",
+        "team2_name",
+        "team2_maps_won",
+        "scheduled_time",
+        "winner",
+        "match_id"
+    ];
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.event.name.clone(),
+            self.event.series.clone(),
+            self.teams[0].name().into_owned(),
+            optional(self.teams[0].maps_won),
+            self.teams[1].name().into_owned(),
+            optional(self.teams[1].maps_won),
+            optional(self.scheduled_time.map(|t| t.to_rfc3339())),
+            optional(self.winner().map(|w| w.name().into_owned())),
+            optional(self.id)
+        ]
+    }
+}
+
+/// A livestream along with which of the `Livestreams` lists it came from.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LivestreamRecord<'a> {
+    pub curated: bool,
+    pub info: &'a LivestreamInfo
+}
+
+impl<'a> LivestreamRecord<'a> {
+    /// Flattens the given streams into records, curated streams first.
+    pub fn from_livestreams(streams: &'a Livestreams) -> Vec<Self> {
+        let curated = streams.curated.iter().map(|info| LivestreamRecord { curated: true, info });
+        let other_top = streams.other_top.iter().map(|info| LivestreamRecord { curated: false, info });
+
+        curated.chain(other_top).collect()
+    }
+}
+
+/// Columns:
+///
+/// | Column | Contents |
+/// |--------|----------|
+/// | `curated` | `true` if the stream is in `Livestreams::curated`, otherwise `false` |
+/// | `name` | `name` |
+/// | `title` | `title` |
+/// | `viewer_count` | `viewer_count` |
+/// | `url` | `url` |
+/// | `platform` | `platform()`, e.g. `Twitch` |
+impl<'a> CsvRecord for LivestreamRecord<'a> {
+    const HEADER: &'static [&'static str] = &[
+        "curated",
+        "name",
+        "title",
+        "viewer_count",
+        "url",
+        "platform"
+    ];
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.curated.to_string(),
+            self.info.name.clone(),
+            optional(self.info.title.clone()),
+            optional(self.info.viewer_count),
+            optional(self.info.url.as_ref()),
+            optional(self.info.platform())
+        ]
+    }
+}
+
+/// A map in a series along with the match it's part of.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MapRecord<'a> {
+    pub state: &'a LiveMatchState,
+    pub map: &'a MapInfo
+}
+
+impl<'a> MapRecord<'a> {
+    /// Flattens the maps of the given match into records, in the order
+    /// they're played.
+    pub fn from_live_state(state: &'a LiveMatchState) -> Vec<Self> {
+        state.maps.iter().map(|map| MapRecord { state, map }).collect()
+    }
+}
+
+/// Columns:
+///
+/// | Column | Contents |
+/// |--------|----------|
+/// | `event_name` | The match's `event.name` |
+/// | `event_series` | The match's `event.series` |
+/// | `team1_name` | The match's `teams[0].name()` |
+/// | `team2_name` | The match's `teams[1].name()` |
+/// | `map` | `name` |
+/// | `team1_score` | `scores[0]` |
+/// | `team2_score` | `scores[1]` |
+/// | `status` | `upcoming`, `in_progress` or `completed` |
+impl<'a> CsvRecord for MapRecord<'a> {
+    const HEADER: &'static [&'static str] = &[
+        "event_name",
+        "event_series",
+        "team1_name",
+        "team2_name",
+        "map",
+        "team1_score",
+        "team2_score",
+        "status"
+    ];
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.state.event.name.clone(),
+            self.state.event.series.clone(),
+            self.state.teams[0].name().into_owned(),
+            self.state.teams[1].name().into_owned(),
+            self.map.name.clone(),
+            optional(self.map.scores[0]),
+            optional(self.map.scores[1]),
+            match self.map.status {
+                MapStatus::Upcoming => "upcoming",
+                MapStatus::InProgress => "in_progress",
+                MapStatus::Completed => "completed"
+            }.into()
+        ]
+    }
+}
+
+/// Writes a header row followed by a row for each of the given records.
+pub fn write<W: Write, R: CsvRecord>(mut writer: W, records: &[R]) -> Result<()> {
+    write_row(&mut writer, R::HEADER)?;
+
+    for record in records {
+        write_row(&mut writer, &record.fields())?;
+    }
+
+    Ok(())
+}
+
+/// Like `write`, but gives you the CSV as a `String`.
+pub fn to_string<R: CsvRecord>(records: &[R]) -> String {
+    let mut buf = vec![];
+    // Writing to a `Vec` can't fail
+    write(&mut buf, records).unwrap();
+    // Everything we write started out as valid UTF-8
+    String::from_utf8(buf).unwrap()
+}
+
+/// Writes the given streams using the `LivestreamRecord` layout.
+pub fn write_livestreams<W: Write>(writer: W, streams: &Livestreams) -> Result<()> {
+    write(writer, &LivestreamRecord::from_livestreams(streams))
+}
+
+fn write_row<W: Write, S: AsRef<str>>(writer: &mut W, fields: &[S]) -> Result<()> {
+    let row: Vec<String> = fields.iter().map(|f| escape(f.as_ref())).collect();
+    writeln!(writer, "{}", row.join(","))?;
+    Ok(())
+}
+
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.into()
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data_structs::{MatchId, TeamSlot};
+    use chrono::{TimeZone, Utc};
+    use url::Url;
+
+    #[test]
+    fn matches_brief_layout() {
+        let mut _match = MatchBriefInfo {
+            id: Some(MatchId(12345)),
+            ..MatchBriefInfo::default()
+        };
+        _match.event.name = "Overwatch League".into();
+        _match.event.series = "Stage 1, Week 2".into();
+        _match.teams[0].slot = TeamSlot::Known("Seoul Dynasty".into());
+        _match.teams[0].maps_won = Some(3);
+        _match.teams[1].slot = TeamSlot::Known("London \"Spitfire\"".into());
+        _match.teams[1].maps_won = Some(1);
+        _match.scheduled_time = Some(Utc.timestamp_opt(1_533_600_000, 0).unwrap());
+
+        assert_eq!(
+            to_string(&[_match, MatchBriefInfo::default()]),
+            "event_name,event_series,team1_name,team1_maps_won,team2_name,team2_maps_won,scheduled_time,winner,match_id\n\
+             Overwatch League,\"Stage 1, Week 2\",Seoul Dynasty,3,\"London \"\"Spitfire\"\"\",1,2018-08-07T00:00:00+00:00,Seoul Dynasty,12345\n\
+             ,,TBD,,TBD,,,,\n"
+        );
+    }
+
+    #[test]
+    fn maps_layout() {
+        let mut state = LiveMatchState::default();
+        state.event.name = "Example Cup".into();
+        state.event.series = "Grand Final".into();
+        state.teams[0].slot = TeamSlot::Known("Alpha".into());
+        state.teams[1].slot = TeamSlot::Known("Bravo".into());
+        state.maps = vec![
+            MapInfo { name: "Ilios".into(), scores: [Some(2), Some(0)], status: MapStatus::Completed },
+            MapInfo { name: "TBD".into(), scores: [None, None], status: MapStatus::Upcoming }
+        ];
+
+        assert_eq!(
+            to_string(&MapRecord::from_live_state(&state)),
+            "event_name,event_series,team1_name,team2_name,map,team1_score,team2_score,status\n\
+             Example Cup,Grand Final,Alpha,Bravo,Ilios,2,0,completed\n\
+             Example Cup,Grand Final,Alpha,Bravo,TBD,,,upcoming\n"
+        );
+    }
+
+    #[test]
+    fn livestreams_layout() {
+        let stream = |name: &str| LivestreamInfo {
+            name: name.into(),
+            title: None,
+            viewer_count: Some(10),
+            url: Url::parse(&format!("https://www.twitch.tv/{}", name)).ok()
+        };
+        let streams = Livestreams {
+            curated: vec![stream("a")],
+            other_top: vec![stream("b")]
+        };
+
+        let mut buf = vec![];
+        write_livestreams(&mut buf, &streams).unwrap();
+
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "curated,name,title,viewer_count,url,platform\n\
+             true,a,,10,https://www.twitch.tv/a,Twitch\n\
+             false,b,,10,https://www.twitch.tv/b,Twitch\n"
+        );
+    }
+}
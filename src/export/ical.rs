@@ -0,0 +1,247 @@
+//! Writes upcoming matches as an iCalendar ([RFC 5545]) feed.
+//!
+//! Each match with a `scheduled_time` becomes an event titled
+//! `Team A vs Team B`, with the event name and series and the streams to watch
+//! it on as its description, and a link to the match page. Event UIDs are
+//! derived from the match's identity rather than its time, so calendar apps
+//! subscribed to a regenerated feed move rescheduled matches instead of
+//! duplicating them.
+//!
+//! [RFC 5545]: https://tools.ietf.org/html/rfc5545
+
+use crate::data_structs::{Livestreams, MatchBriefInfo};
+use crate::error::*;
+use crate::site::Site;
+use chrono::{DateTime, Utc};
+use std::io::Write;
+
+/// How long a match is assumed to last, as the site doesn't say.
+pub const MATCH_DURATION: &str = "PT2H";
+
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Writes a calendar containing an event for each of the given matches that
+/// has a `scheduled_time`, linking to the match pages on `site`.
+///
+/// The site doesn't say which stream shows which match, so the curated
+/// streams in `streams` (such as the leagues' own broadcasts, see
+/// `MainPageScraper::livestreams`) that have a URL are listed in every
+/// event's description.
+///
+/// `generated_at` is used as the `DTSTAMP` of every event, and should usually
+/// be the current time.
+pub fn write<W: Write>(
+    mut writer: W,
+    site: &Site,
+    matches: &[MatchBriefInfo],
+    streams: &Livestreams,
+    generated_at: DateTime<Utc>
+) -> Result<()> {
+    let dtstamp = generated_at.format(DATE_TIME_FORMAT).to_string();
+
+    write_line(&mut writer, "BEGIN:VCALENDAR")?;
+    write_line(&mut writer, "VERSION:2.0")?;
+    write_line(&mut writer, "PRODID:-//overgg-scraper//EN")?;
+    write_line(&mut writer, "CALSCALE:GREGORIAN")?;
+    write_line(&mut writer, "X-WR-CALNAME:over.gg upcoming matches")?;
+
+    for _match in matches {
+        let start = match _match.scheduled_time {
+            Some(time) => time,
+            None => continue
+        };
+
+        write_line(&mut writer, "BEGIN:VEVENT")?;
+        write_line(&mut writer, &format!("UID:{}", uid(_match)))?;
+        write_line(&mut writer, &format!("DTSTAMP:{}", dtstamp))?;
+        write_line(&mut writer, &format!("DTSTART:{}", start.format(DATE_TIME_FORMAT)))?;
+        write_line(&mut writer, &format!("DURATION:{}", MATCH_DURATION))?;
+        write_line(&mut writer, &format!(
+            "SUMMARY:{} vs {}",
+            escape(&_match.teams[0].name()),
+            escape(&_match.teams[1].name())
+        ))?;
+        write_line(&mut writer, &format!("DESCRIPTION:{}", escape(&description(_match, streams))))?;
+
+        if let Some(id) = _match.id {
+            write_line(&mut writer, &format!("URL:{}", site.match_page(id)))?;
+        }
+
+        write_line(&mut writer, "END:VEVENT")?;
+    }
+
+    write_line(&mut writer, "END:VCALENDAR")?;
+    Ok(())
+}
+
+/// Like `write`, but gives you the calendar as a `String`.
+pub fn to_string(site: &Site, matches: &[MatchBriefInfo], streams: &Livestreams, generated_at: DateTime<Utc>) -> String {
+    let mut buf = vec![];
+    // Writing to a `Vec` can't fail
+    write(&mut buf, site, matches, streams, generated_at).unwrap();
+    // Everything we write started out as valid UTF-8
+    String::from_utf8(buf).unwrap()
+}
+
+/// A UID that stays the same for a match across regenerations of the feed.
+fn uid(_match: &MatchBriefInfo) -> String {
+    format!("match-{}@over.gg", _match.stable_key())
+}
+
+fn description(_match: &MatchBriefInfo, streams: &Livestreams) -> String {
+    let mut description = match (_match.event.name.is_empty(), _match.event.series.is_empty()) {
+        (false, false) => format!("{}: {}", _match.event.name, _match.event.series),
+        (false, true) => _match.event.name.clone(),
+        (true, _) => _match.event.series.clone()
+    };
+
+    let mut with_urls = streams.curated.iter()
+        .filter_map(|stream| stream.url.as_ref().map(|url| (&stream.name, url)))
+        .peekable();
+
+    if with_urls.peek().is_some() {
+        if !description.is_empty() {
+            description.push_str("\n\n");
+        }
+
+        description.push_str("Streams:");
+
+        for (name, url) in with_urls {
+            description.push_str(&format!("\n{}: {}", name, url));
+        }
+    }
+
+    description
+}
+
+/// Escapes the given text for use in a `TEXT` property value.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Writes a content line, folding it so that no line exceeds 75 octets.
+fn write_line<W: Write>(writer: &mut W, line: &str) -> Result<()> {
+    let mut start = 0;
+    // The first line gets 75 octets, the rest lose one to the leading space
+    let mut limit = 75;
+
+    while line.len() - start > limit {
+        let mut end = start + limit;
+
+        while !line.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        write!(writer, "{}\r\n ", &line[start..end])?;
+        start = end;
+        limit = 74;
+    }
+
+    write!(writer, "{}\r\n", &line[start..])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data_structs::{LivestreamInfo, MatchId, TeamSlot};
+    use chrono::TimeZone;
+    use url::Url;
+
+    fn upcoming(id: Option<MatchId>, ts: i64) -> MatchBriefInfo {
+        let mut _match = MatchBriefInfo {
+            id,
+            scheduled_time: Some(Utc.timestamp_opt(ts, 0).unwrap()),
+            ..MatchBriefInfo::default()
+        };
+        _match.event.name = "Overwatch League".into();
+        _match.event.series = "Stage 1, Week 2".into();
+        _match.teams[0].slot = TeamSlot::Known("Seoul Dynasty".into());
+        _match.teams[1].slot = TeamSlot::Tbd;
+        _match
+    }
+
+    #[test]
+    fn calendar() {
+        let generated_at = Utc.timestamp_opt(1_533_500_000, 0).unwrap();
+        let mut unscheduled = upcoming(None, 0);
+        unscheduled.scheduled_time = None;
+
+        let ics = to_string(
+            &Site::default(),
+            &[upcoming(Some(MatchId(12345)), 1_533_600_000), unscheduled],
+            &Livestreams::default(),
+            generated_at
+        );
+
+        assert_eq!(ics, "BEGIN:VCALENDAR\r\n\
+                         VERSION:2.0\r\n\
+                         PRODID:-//overgg-scraper//EN\r\n\
+                         CALSCALE:GREGORIAN\r\n\
+                         X-WR-CALNAME:over.gg upcoming matches\r\n\
+                         BEGIN:VEVENT\r\n\
+                         UID:match-12345@over.gg\r\n\
+                         DTSTAMP:20180805T201320Z\r\n\
+                         DTSTART:20180807T000000Z\r\n\
+                         DURATION:PT2H\r\n\
+                         SUMMARY:Seoul Dynasty vs TBD\r\n\
+                         DESCRIPTION:Overwatch League: Stage 1\\, Week 2\r\n\
+                         URL:https://www.over.gg/12345\r\n\
+                         END:VEVENT\r\n\
+                         END:VCALENDAR\r\n");
+    }
+
+    #[test]
+    fn site_and_streams() {
+        let stream = |name: &str, url: Option<&str>| LivestreamInfo {
+            name: name.into(),
+            url: url.map(|url| Url::parse(url).unwrap()),
+            ..LivestreamInfo::default()
+        };
+        let streams = Livestreams {
+            curated: vec![
+                stream("OWL", Some("https://www.twitch.tv/overwatchleague")),
+                stream("No URL", None),
+                stream("OWL (YouTube)", Some("https://www.youtube.com/overwatchleague"))
+            ],
+            other_top: vec![stream("Someone", Some("https://www.twitch.tv/someone"))]
+        };
+        let mirror = Site::new("http://127.0.0.1:8080/overgg").unwrap();
+
+        let ics = to_string(&mirror, &[upcoming(Some(MatchId(12345)), 1_533_600_000)], &streams, Utc::now());
+        let lines: Vec<&str> = ics.split("\r\n").collect();
+        let unfolded = ics.replace("\r\n ", "");
+
+        assert!(lines.iter().all(|line| line.len() <= 75));
+        assert!(unfolded.contains(
+            "DESCRIPTION:Overwatch League: Stage 1\\, Week 2\\n\\n\
+             Streams:\\nOWL: https://www.twitch.tv/overwatchleague\\n\
+             OWL (YouTube): https://www.youtube.com/overwatchleague\r\n"
+        ));
+        assert!(unfolded.contains("URL:http://127.0.0.1:8080/overgg/12345\r\n"));
+        assert!(!unfolded.contains("Someone"));
+    }
+
+    #[test]
+    fn uid_is_stable_across_reschedules() {
+        let before = uid(&upcoming(None, 1_533_600_000));
+        let after = uid(&upcoming(None, 1_533_700_000));
+
+        assert_eq!(before, after);
+        assert_ne!(before, uid(&MatchBriefInfo::default()));
+    }
+
+    #[test]
+    fn long_lines_are_folded() {
+        let mut buf = vec![];
+        write_line(&mut buf, &"é".repeat(60)).unwrap();
+        let folded = String::from_utf8(buf).unwrap();
+
+        assert!(folded.split("\r\n").all(|line| line.len() <= 75));
+        assert_eq!(folded.replace("\r\n ", ""), format!("{}\r\n", "é".repeat(60)));
+    }
+}
@@ -0,0 +1,5 @@
+//! Conversions of scraped data into formats that are useful outside of Rust.
+
+pub mod atom;
+pub mod csv;
+pub mod ical;
@@ -0,0 +1,32 @@
+//! An abstraction over where pages come from.
+//!
+//! Anything that builds on top of fetching pages (such as the `crawler`) is
+//! written against the `Fetcher` trait, so that it works the same whether the
+//! pages come from the live site (`http_client::Downloader`) or somewhere else.
+
+use crate::error::*;
+use chrono::{DateTime, Utc};
+
+/// A page that has been fetched.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Page {
+    /// The URL that was requested.
+    pub url: String,
+    pub fetched_at: DateTime<Utc>,
+    /// The response headers, in the order they were received.
+    pub headers: Vec<(String, String)>,
+    pub body: String
+}
+
+/// Something that can fetch pages.
+pub trait Fetcher {
+    /// Fetches the page at the given URL.
+    fn fetch(&self, url: &str) -> Result<Page>;
+}
+
+impl<F: Fetcher + ?Sized> Fetcher for &F {
+    #[inline]
+    fn fetch(&self, url: &str) -> Result<Page> {
+        (**self).fetch(url)
+    }
+}
@@ -0,0 +1,340 @@
+//! Head-to-head history between two teams, for match previews.
+//!
+//! `HeadToHead::collect` fetches the pages of the two teams with the given IDs
+//! and collects the completed matches listed on them:
+//!
+//! ```no_run
+//! # use overgg_scraper::data_structs::TeamId;
+//! # use overgg_scraper::fetch::Fetcher;
+//! # use overgg_scraper::head_to_head::HeadToHead;
+//! # use overgg_scraper::site::Site;
+//! # fn preview<F: Fetcher>(fetcher: F) -> overgg_scraper::error::Result<()> {
+//! // `fetcher` would usually be an `http_client::Downloader`
+//! let h2h = HeadToHead::collect(fetcher, &Site::default(), TeamId(12), TeamId(34))?;
+//!
+//! for _match in h2h.recent(5) {
+//!     println!("{:?}", _match.teams);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A `HeadToHead` can also be built from any other collection of completed
+//! matches, whether they were just scraped, loaded from `storage` or replayed
+//! from an `archive`. Brief match info doesn't carry team IDs, so within the
+//! matches teams are told apart by name (ignoring case and surrounding
+//! whitespace).
+//!
+//! Brief match info only has series scores, so per-map records come from match
+//! pages (see `scrapers::match_page`) added with `HeadToHead::add_match_page`.
+
+use crate::data_structs::{LiveMatchState, MatchBriefInfo, MatchId, Team, TeamCompletedMatchBriefInfo, TeamId, TeamPageInfo};
+use crate::error::*;
+use crate::fetch::Fetcher;
+use crate::scrapers::team_page::TeamPageScraper;
+use crate::site::Site;
+use crate::stats::{known_teams, team_key, Record};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The history between two teams, from the point of view of the first.
+#[derive(Default, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub struct HeadToHead {
+    pub team: String,
+    pub opponent: String,
+    /// The teams' IDs, if the history was collected from their pages.
+    pub team_id: Option<TeamId>,
+    pub opponent_id: Option<TeamId>,
+    /// Series won, lost and drawn.
+    pub series: Record,
+    /// Maps won and lost, counted from the series scores.
+    ///
+    /// Series scores don't show drawn maps, so there are never any draws.
+    pub maps: Record,
+    /// Records on each map by name, from the match pages that were added.
+    pub by_map: BTreeMap<String, Record>,
+    /// The matches between the teams, most recent first.
+    ///
+    /// Matches without a scheduled time come last. Each match is only here
+    /// (and counted in the records) once, even if it was passed in more than
+    /// once.
+    pub meetings: Vec<MatchBriefInfo>,
+    /// The match pages that have been added to `by_map`.
+    pub match_pages: BTreeSet<MatchId>
+}
+
+impl HeadToHead {
+    /// Collects the history between the teams with the given IDs from the
+    /// completed matches listed on their team pages, fetched from `site`.
+    ///
+    /// Fails if either page can't be fetched or scraped in full.
+    pub fn collect<F: Fetcher>(fetcher: F, site: &Site, team: TeamId, opponent: TeamId) -> Result<Self> {
+        let team_page = |id| -> Result<TeamPageInfo> {
+            let page = fetcher.fetch(site.team_page(id).as_str())?;
+
+            TeamPageScraper::from(page)
+                .with_site(site.clone())
+                .with_id(id)
+                .team_info_strict()
+        };
+
+        Ok(HeadToHead::from_team_pages(&team_page(team)?, &team_page(opponent)?))
+    }
+
+    /// Collects the history between two teams from the completed matches
+    /// listed on their team pages.
+    ///
+    /// A match between the teams is usually listed on both pages, but is only
+    /// counted once.
+    pub fn from_team_pages(team: &TeamPageInfo, opponent: &TeamPageInfo) -> Self {
+        HeadToHead {
+            team_id: team.id,
+            opponent_id: opponent.id,
+            ..HeadToHead::from_matches(&team.name, &opponent.name, team.matches.iter().chain(&opponent.matches))
+        }
+    }
+
+    /// Collects the history between the two named teams from the given
+    /// matches.
+    ///
+    /// Matches that don't have a score for both teams are left out, but ones
+    /// that are still in progress aren't told apart from completed ones, so
+    /// only pass in completed matches (e.g. from
+    /// `MainPageScraper::matches_brief(Completed)`). Matches are told apart by
+    /// `MatchBriefInfo::id`, so ones without an ID are always counted.
+    pub fn from_matches<'a, I>(team: &str, opponent: &str, matches: I) -> Self
+        where I: IntoIterator<Item = &'a MatchBriefInfo> {
+
+        let mut head_to_head = HeadToHead {
+            team: team.trim().into(),
+            opponent: opponent.trim().into(),
+            ..HeadToHead::default()
+        };
+        let mut seen = BTreeSet::new();
+
+        for _match in matches {
+            let side = match head_to_head.side(&_match.teams) {
+                Some(side) => side,
+                None => continue
+            };
+
+            let (score, opponent_score) = match (_match.teams[0].maps_won, _match.teams[1].maps_won) {
+                (Some(a), Some(b)) => oriented(side, u32::from(a), u32::from(b)),
+                _ => continue
+            };
+
+            if let Some(id) = _match.id {
+                if !seen.insert(id) {
+                    continue;
+                }
+            }
+
+            head_to_head.series.add(score, opponent_score);
+            head_to_head.maps.wins += score;
+            head_to_head.maps.losses += opponent_score;
+            head_to_head.meetings.push(_match.clone());
+        }
+
+        // `None` sorts first, so reversing puts matches without a time last
+        head_to_head.meetings.sort_by_key(|_match| Reverse(_match.scheduled_time));
+
+        head_to_head
+    }
+
+    /// The most recent `count` meetings.
+    #[inline]
+    pub fn recent(&self, count: usize) -> &[MatchBriefInfo] {
+        &self.meetings[..count.min(self.meetings.len())]
+    }
+
+    /// Adds the maps completed in a match between the teams to `by_map`.
+    ///
+    /// Returns whether anything was added, which it isn't if the match
+    /// wasn't between the teams or its page has been added already. Pages
+    /// are told apart by `LiveMatchState::id`, so ones without an ID are
+    /// always added.
+    pub fn add_match_page(&mut self, state: &LiveMatchState) -> bool {
+        let side = match self.side(&state.teams) {
+            Some(side) => side,
+            None => return false
+        };
+
+        if let Some(id) = state.id {
+            if !self.match_pages.insert(id) {
+                return false;
+            }
+        }
+
+        for map in state.completed_maps() {
+            let record = self.by_map.entry(map.name.clone()).or_default();
+
+            match map.winner() {
+                Some(winner) if winner == side => record.wins += 1,
+                Some(_) => record.losses += 1,
+                None => record.draws += 1
+            }
+        }
+
+        true
+    }
+
+    /// Which of a match's teams is `team`, if both are known and they're the
+    /// two teams this is about.
+    fn side(&self, teams: &[TeamCompletedMatchBriefInfo; 2]) -> Option<Team> {
+        let [first, second] = known_teams(teams)?;
+
+        if same_team(first, &self.team) && same_team(second, &self.opponent) {
+            Some(Team::Zero)
+        } else if same_team(first, &self.opponent) && same_team(second, &self.team) {
+            Some(Team::One)
+        } else {
+            None
+        }
+    }
+}
+
+fn same_team(a: &str, b: &str) -> bool {
+    team_key(a) == team_key(b)
+}
+
+/// Puts the score of the team on the given side first.
+fn oriented(side: Team, first: u32, second: u32) -> (u32, u32) {
+    match side {
+        Team::Zero => (first, second),
+        Team::One => (second, first)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::known_team;
+    use crate::data_structs::{MapInfo, MapStatus, TeamSlot};
+    use chrono::{TimeZone, Utc};
+
+    fn played(a: (&str, u8), b: (&str, u8), timestamp: Option<i64>) -> MatchBriefInfo {
+        MatchBriefInfo {
+            teams: [
+                known_team(a.0, Some(a.1)),
+                known_team(b.0, Some(b.1))
+            ],
+            scheduled_time: timestamp.map(|ts| Utc.timestamp_opt(ts, 0).unwrap()),
+            ..MatchBriefInfo::default()
+        }
+    }
+
+    #[test]
+    fn records_and_meetings() {
+        let matches = vec![
+            played(("Alpha", 3), ("Bravo", 1), Some(100)),
+            played(("Bravo", 3), ("alpha ", 2), Some(300)),
+            played(("Alpha", 2), ("Bravo", 2), None),
+            played(("Alpha", 3), ("Charlie", 0), Some(400)),
+            // Not played yet
+            MatchBriefInfo {
+                teams: [
+                    known_team("Alpha", None),
+                    known_team("Bravo", None)
+                ],
+                ..MatchBriefInfo::default()
+            },
+            played(("Alpha", 3), ("Bravo", 0), Some(200))
+        ];
+
+        let h2h = HeadToHead::from_matches("Alpha", "Bravo", &matches);
+
+        assert_eq!(h2h.series, Record { wins: 2, losses: 1, draws: 1 });
+        assert_eq!(h2h.maps, Record { wins: 10, losses: 6, draws: 0 });
+        assert_eq!(h2h.series.win_rate(), Some(0.5));
+
+        let times: Vec<Option<i64>> = h2h.meetings.iter().map(|m| m.scheduled_time.map(|t| t.timestamp())).collect();
+        assert_eq!(times, vec![Some(300), Some(200), Some(100), None]);
+        assert_eq!(h2h.recent(2).len(), 2);
+        assert_eq!(h2h.recent(10).len(), 4);
+
+        // The other way around
+        let reversed = HeadToHead::from_matches("Bravo", "Alpha", &matches);
+        assert_eq!(reversed.series, Record { wins: 1, losses: 2, draws: 1 });
+
+        let strangers = HeadToHead::from_matches("Alpha", "Delta", &matches);
+        assert_eq!(strangers.series.played(), 0);
+        assert_eq!(strangers.series.win_rate(), None);
+
+        // A team that isn't known isn't one called "TBD"
+        let mut undecided = played(("Alpha", 3), ("TBD", 0), None);
+        undecided.teams[1].slot = TeamSlot::Tbd;
+        assert_eq!(HeadToHead::from_matches("Alpha", "TBD", &[undecided]).series.played(), 0);
+    }
+
+    #[test]
+    fn matches_counted_once() {
+        let mut final_match = played(("Alpha", 3), ("Bravo", 1), Some(100));
+        final_match.id = Some(MatchId(1));
+        let mut rematch = played(("Bravo", 3), ("Alpha", 0), Some(200));
+        rematch.id = Some(MatchId(2));
+
+        let team = TeamPageInfo {
+            id: Some(TeamId(1)),
+            name: "Alpha".into(),
+            matches: vec![final_match.clone(), played(("Alpha", 2), ("Bravo", 0), None)],
+            ..TeamPageInfo::default()
+        };
+        let opponent = TeamPageInfo {
+            id: Some(TeamId(2)),
+            name: "Bravo".into(),
+            matches: vec![rematch, final_match],
+            ..TeamPageInfo::default()
+        };
+
+        let h2h = HeadToHead::from_team_pages(&team, &opponent);
+        assert_eq!((h2h.team_id, h2h.opponent_id), (Some(TeamId(1)), Some(TeamId(2))));
+        assert_eq!(h2h.series, Record { wins: 2, losses: 1, draws: 0 });
+        assert_eq!(h2h.maps, Record { wins: 5, losses: 4, draws: 0 });
+
+        let ids: Vec<Option<MatchId>> = h2h.meetings.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![Some(MatchId(2)), Some(MatchId(1)), None]);
+    }
+
+    #[test]
+    fn map_records() {
+        let map = |name: &str, scores: [u8; 2], status: MapStatus| MapInfo {
+            name: name.into(),
+            scores: [Some(scores[0]), Some(scores[1])],
+            status
+        };
+        let state = LiveMatchState {
+            id: Some(MatchId(1)),
+            teams: [
+                known_team("Bravo", Some(2)),
+                known_team("Alpha", Some(1))
+            ],
+            maps: vec![
+                map("Ilios", [2, 0], MapStatus::Completed),
+                map("King's Row", [2, 3], MapStatus::Completed),
+                map("Hanamura", [4, 3], MapStatus::Completed),
+                map("Ilios", [1, 0], MapStatus::InProgress)
+            ],
+            ..LiveMatchState::default()
+        };
+
+        let mut h2h = HeadToHead::from_matches("Alpha", "Bravo", &[]);
+        assert!(h2h.add_match_page(&state));
+        assert!(!h2h.add_match_page(&LiveMatchState::default()));
+
+        // The same match scraped again
+        assert!(!h2h.add_match_page(&state));
+
+        assert_eq!(h2h.by_map["Ilios"], Record { wins: 0, losses: 1, draws: 0 });
+        assert_eq!(h2h.by_map["King's Row"], Record { wins: 1, losses: 0, draws: 0 });
+        assert_eq!(h2h.by_map.len(), 3);
+
+        let rematch = LiveMatchState {
+            id: Some(MatchId(2)),
+            ..state
+        };
+        assert!(h2h.add_match_page(&rematch));
+        assert_eq!(h2h.by_map["Ilios"], Record { wins: 0, losses: 2, draws: 0 });
+        assert_eq!(h2h.match_pages.len(), 2);
+    }
+}
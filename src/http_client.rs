@@ -3,13 +3,30 @@
 //! This module can be disabled by building this crate without default features.
 
 use crate::error::*;
-use reqwest::{Client, IntoUrl};
+use crate::fetch::{Fetcher, Page};
+use chrono::Utc;
+use reqwest::Client;
+use crate::data_structs::{EventId, MatchId, TeamId};
+use crate::scrapers::event_page::EventPageScraper;
 use crate::scrapers::main_page::MainPageScraper;
+use crate::scrapers::match_page::MatchPageScraper;
+use crate::scrapers::team_page::TeamPageScraper;
+use crate::site::Site;
 use std::io::Read;
 
 /// A quick, built-in way to grab HTML from the live site.
+///
+/// It can also be pointed at any other copy of the site with `with_site`.
 pub struct Downloader {
-    client: Client
+    client: Client,
+    site: Site
+}
+
+impl Default for Downloader {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Downloader {
@@ -17,7 +34,8 @@ impl Downloader {
     #[inline]
     pub fn new() -> Self {
         Self {
-            client: Client::new()
+            client: Client::new(),
+            site: Site::default()
         }
     }
 
@@ -28,31 +46,76 @@ impl Downloader {
     #[inline]
     pub fn with_client(client: Client) -> Self {
         Self {
-            client
+            client,
+            site: Site::default()
         }
     }
 
-    /// Obtain a scraper for the main page (https://www.over.gg/).
+    /// Fetch pages from the given site instead of the live one.
+    #[inline]
+    pub fn with_site(mut self, site: Site) -> Self {
+        self.site = site;
+        self
+    }
+
+    /// The site pages are fetched from.
+    #[inline]
+    pub fn site(&self) -> &Site {
+        &self.site
+    }
+
+    /// Obtain a scraper for the main page (https://www.over.gg/ unless
+    /// another site was given).
     #[inline]
     pub fn main_page(&self) -> Result<MainPageScraper> {
-        Ok(self.get_string("https://www.over.gg/")?.into())
+        let page = self.fetch(self.site.main_page().as_str())?;
+        Ok(MainPageScraper::from(page).with_site(self.site.clone()))
     }
 
-    /// Helper to get the HTML of the given URL.
-    ///
-    /// Panics if the given `url` cannot be parsed.
+    /// Obtain a scraper for the page of the match with the given ID.
     #[inline]
-    fn get_string<U: IntoUrl>(&self, url: U) -> Result<String> {
+    pub fn match_page(&self, id: MatchId) -> Result<MatchPageScraper> {
+        let page = self.fetch(self.site.match_page(id).as_str())?;
+        Ok(MatchPageScraper::from(page).with_site(self.site.clone()))
+    }
+
+    /// Obtain a scraper for the page of the team with the given ID.
+    #[inline]
+    pub fn team_page(&self, id: TeamId) -> Result<TeamPageScraper> {
+        let page = self.fetch(self.site.team_page(id).as_str())?;
+        Ok(TeamPageScraper::from(page).with_site(self.site.clone()))
+    }
+
+    /// Obtain a scraper for the page of the event with the given ID.
+    #[inline]
+    pub fn event_page(&self, id: EventId) -> Result<EventPageScraper> {
+        let page = self.fetch(self.site.event_page(id).as_str())?;
+        Ok(EventPageScraper::from(page).with_site(self.site.clone()))
+    }
+}
+
+impl Fetcher for Downloader {
+    fn fetch(&self, url: &str) -> Result<Page> {
         let mut resp = self.client.get(url).send()?;
         let status = resp.status();
 
         if !status.is_success() {
-            bail!(ErrorKind::NonSuccessStatus(status));
+            return Err(Error::NonSuccessStatus(status));
         }
 
-        let mut content = String::new();
-        resp.read_to_string(&mut content)?;
+        let headers = resp.headers()
+            .iter()
+            .filter_map(|(name, val)| val.to_str().ok().map(|val| (name.as_str().into(), val.into())))
+            .collect();
+
+        let mut body = String::new();
+        resp.read_to_string(&mut body)?;
 
-        Ok(content)
+        Ok(Page {
+            url: url.into(),
+            fetched_at: Utc::now(),
+            headers,
+            body
+        })
     }
 }
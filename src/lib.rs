@@ -16,15 +16,39 @@ I do not currently intend to publish this on crates.io as it is a very niche cra
 
 ## Testing
 
-There are currently two kinds of tests that this library employs: one that
-grabs HTML from the live site and runs some assertions on the output, and
-another that uses locally provided HTML files to ensure that extraction
-of data from the HTML does not change. The local dataset is not staged in
-the repo in an attempt to remain legal :), so you will have to provide your
-own dataset in order to run the local test variety. This is currently as simple
-as saving the [over.gg](https://www.over.gg/) page HTML to
-`test_data/www.over.gg.html` via your web browser. If more data extraction gets
-added in the future you will likely have to provide more pages.
+A plain `cargo test` runs offline. The scrapers are tested against
+hand-written pages in `tests/fixtures/` that mirror the site's structure
+without containing any of its content.
+
+There are two more kinds of tests that this library employs: one that grabs
+HTML from the live site and runs some assertions on the output (enabled by the
+`test-live-site` feature), and another that uses locally provided HTML files
+to ensure that extraction of data from real pages does not change. The local
+dataset is not staged in the repo in an attempt to remain legal :), so you
+will have to provide your own dataset in order to run the local test variety.
+Either save the
+[over.gg](https://www.over.gg/) page HTML to `test_data/www.over.gg.html` via
+your web browser, or have the tests download it for you:
+
+```text
+OVERGG_RECORD=1 OVERGG_BLESS=1 cargo test --features test-local-data
+```
+
+`OVERGG_RECORD` saves fresh copies of the pages to `test_data/`, and
+`OVERGG_BLESS` regenerates the expected output stored alongside them. Without
+`OVERGG_BLESS`, a test whose extraction output has changed fails with a diff
+of the expected and actual output; bless it once you've checked the change is
+intended.
+
+`tests/extractor_properties.rs` runs the extractors over randomized variants
+of the card markup, and `fuzz/` holds a [`cargo-fuzz`] target that feeds
+arbitrary HTML to every scraper (`cargo +nightly fuzz run scrapers`).
+
+[`cargo-fuzz`]: https://github.com/rust-fuzz/cargo-fuzz
+
+The `wasm` bindings have their own tests in `tests/wasm.rs`, which run under a
+headless wasm runtime with
+`wasm-pack test --node -- --no-default-features --features wasm`.
 
 ## Rust Version Support
 
@@ -33,13 +57,25 @@ compile on an older version.
 
 ## Cargo Features
 
-* `http_client` toggles compilation of the `http_client` module.
+* `http-client` toggles compilation of the `http_client` module. Without it
+  the crate has no HTTP dependencies; you provide the HTML yourself and the
+  parsing core (`scrapers` and `data_structs`) works the same.
+* `wasm` toggles compilation of the `wasm` module, which exposes the scrapers
+  to JavaScript through `wasm-bindgen`. Use it without default features.
+* `storage` toggles compilation of the `storage` module, which persists
+  scraped data in a SQLite database.
+* `timezones` adds `MatchBriefInfo::scheduled_time_in_zone`, which looks up
+  IANA time zones by name using `chrono-tz`.
+* `cli` toggles compilation of the `overgg` command-line tool, which prints
+  the scraped data as a table, JSON or CSV (`overgg --help` for details).
 * `derive-serde` toggles derivation of `serde`'s `Serialize` and `Deserialize`
-traits for appropriate data structures, both in this crate and its dependencies.
+  traits for appropriate data structures, both in this crate and its dependencies.
 * `test-local-data` is for the development of the library, enabling tests that
-utilize local HTML files.
+  utilize local HTML files.
+* `test-live-site` is for the development of the library, enabling tests that
+  scrape the live site.
 
-The `http_client` feature is enabled by default.
+The `http-client` feature is enabled by default.
 
 ## Disclaimer
 
@@ -48,27 +84,37 @@ programmatic retrieval of data from the [over.gg](https://www.over.gg/) site,
 nothing more, and I do not endorse excessive scraping.
 */
 
-#![recursion_limit = "1024"]
-
-#[macro_use]
-extern crate error_chain;
-#[macro_use]
-extern crate if_chain;
 #[cfg(feature = "http-client")]
 extern crate reqwest;
 extern crate scraper;
 extern crate chrono;
 extern crate toml;
+extern crate url;
 extern crate serde;
-#[cfg_attr(feature = "test-local-data", macro_use)]
+#[cfg_attr(feature = "derive-serde", macro_use)]
 extern crate serde_derive;
-#[cfg(all(feature = "test-local-data", test))]
+#[cfg(feature = "serde_json")]
 extern crate serde_json;
+#[cfg(feature = "timezones")]
+extern crate chrono_tz;
 
 pub mod error;
 pub mod data_structs;
 pub mod scrapers;
+pub mod export;
+pub mod site;
+pub mod fetch;
+pub mod crawler;
+pub mod archive;
+pub mod head_to_head;
+pub mod stats;
+pub mod rating;
+pub mod teams;
+#[cfg(feature = "http-client")]
 pub mod http_client;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "storage")]
+pub mod storage;
 #[cfg(test)]
-#[cfg(feature = "test-local-data")]
 mod test_utils;
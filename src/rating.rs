@@ -0,0 +1,388 @@
+//! Elo ratings for teams, worked out from completed matches.
+//!
+//! ```rust
+//! # use overgg_scraper::data_structs::MatchBriefInfo;
+//! # use overgg_scraper::rating::{Elo, RatingMode};
+//! # let completed: Vec<MatchBriefInfo> = vec![];
+//! # let upcoming = MatchBriefInfo::default();
+//! let mut elo = Elo::new().k_factor(24.0).mode(RatingMode::Maps);
+//! elo.ingest(&completed);
+//!
+//! for team in elo.rankings() {
+//!     println!("{}: {:.0}", team.team, team.rating);
+//! }
+//!
+//! // `None` if either team hasn't played
+//! let chance = elo.predict_series(&upcoming, 5);
+//! ```
+//!
+//! As elsewhere, teams are told apart by name (ignoring case and surrounding
+//! whitespace).
+
+use crate::data_structs::MatchBriefInfo;
+use crate::stats::{known_teams, team_key};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// What counts as a game when updating ratings.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub enum RatingMode {
+    /// Each series is one game, however many maps it went to.
+    Series,
+    /// Each map is a game, so a 3-0 moves ratings more than a 3-2.
+    Maps
+}
+
+/// A team's rating and how it got there.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub struct TeamRating {
+    /// The team's name as it was first seen.
+    pub team: String,
+    pub rating: f64,
+    /// Every change to the rating, oldest first.
+    pub history: Vec<RatingChange>
+}
+
+/// A change to a team's rating from one match.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub struct RatingChange {
+    /// When the match was scheduled, if that's known.
+    pub time: Option<DateTime<Utc>>,
+    pub opponent: String,
+    /// How much the rating went up (or down, if negative).
+    pub change: f64,
+    /// The rating after the change.
+    pub rating: f64
+}
+
+/// Elo ratings for every team in the matches it's been given.
+///
+/// Defaults to a K-factor of 32, an initial rating of 1500 and
+/// `RatingMode::Series`.
+#[derive(Debug, Clone)]
+pub struct Elo {
+    k_factor: f64,
+    initial_rating: f64,
+    mode: RatingMode,
+    teams: HashMap<String, TeamRating>
+}
+
+impl Default for Elo {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Elo {
+    pub fn new() -> Self {
+        Self {
+            k_factor: 32.0,
+            initial_rating: 1500.0,
+            mode: RatingMode::Series,
+            teams: HashMap::new()
+        }
+    }
+
+    /// Set how far a single game can move a rating.
+    #[inline]
+    pub fn k_factor(mut self, k_factor: f64) -> Self {
+        self.k_factor = k_factor;
+        self
+    }
+
+    /// Set the rating teams start out with.
+    #[inline]
+    pub fn initial_rating(mut self, initial_rating: f64) -> Self {
+        self.initial_rating = initial_rating;
+        self
+    }
+
+    /// Set what counts as a game.
+    #[inline]
+    pub fn mode(mut self, mode: RatingMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Updates the ratings with the results of the given matches.
+    ///
+    /// The matches are taken in the order they were scheduled, and matches
+    /// without a time count as the oldest. Matches where either team isn't
+    /// known or doesn't have a score are skipped, but ones that are still in progress
+    /// aren't told apart from completed ones, so only pass in completed
+    /// matches.
+    ///
+    /// Each call carries on from the ratings so far, so matches given in a
+    /// later call count as played after the ones given before.
+    pub fn ingest<'a, I>(&mut self, matches: I)
+        where I: IntoIterator<Item = &'a MatchBriefInfo> {
+
+        let mut matches: Vec<&MatchBriefInfo> = matches.into_iter().collect();
+        // Stable, so matches at the same time stay in the order given
+        matches.sort_by_key(|_match| _match.scheduled_time);
+
+        for _match in matches {
+            let names = known_teams(&_match.teams);
+
+            if let (Some(names), Some(a), Some(b)) = (names, _match.teams[0].maps_won, _match.teams[1].maps_won) {
+                self.update(_match, names, f64::from(a), f64::from(b));
+            }
+        }
+    }
+
+    /// The team's current rating, which is the initial rating if it hasn't
+    /// played.
+    #[inline]
+    pub fn rating(&self, team: &str) -> f64 {
+        self.team(team).map_or(self.initial_rating, |team| team.rating)
+    }
+
+    /// The team's rating and its history, if it's played.
+    #[inline]
+    pub fn team(&self, team: &str) -> Option<&TeamRating> {
+        self.teams.get(&team_key(team))
+    }
+
+    /// Every team that's played, highest rated first.
+    pub fn rankings(&self) -> Vec<&TeamRating> {
+        let mut teams: Vec<&TeamRating> = self.teams.values().collect();
+        teams.sort_by(|a, b| b.rating.total_cmp(&a.rating).then_with(|| a.team.cmp(&b.team)));
+        teams
+    }
+
+    /// The chance that the first team wins a game of the given match, from
+    /// 0 to 1.
+    ///
+    /// A game is what `RatingMode` says it is: the whole series in
+    /// `RatingMode::Series`, but a single map in `RatingMode::Maps` (see
+    /// `predict_series` for the chance of winning the series).
+    ///
+    /// This is `None` unless both teams are known and have played, as
+    /// there's nothing to go on otherwise.
+    pub fn predict(&self, _match: &MatchBriefInfo) -> Option<f64> {
+        let [first, second] = known_teams(&_match.teams)?;
+
+        Some(expected(self.team(first)?.rating, self.team(second)?.rating))
+    }
+
+    /// The chance that the first team wins the given match, which is played
+    /// as a best-of-`best_of` series, from 0 to 1.
+    ///
+    /// In `RatingMode::Maps` the chance of winning each map is carried
+    /// through the series, assuming maps are independent and never drawn.
+    /// In `RatingMode::Series` this is the same as `predict`.
+    pub fn predict_series(&self, _match: &MatchBriefInfo, best_of: u32) -> Option<f64> {
+        let chance = self.predict(_match)?;
+
+        Some(match self.mode {
+            RatingMode::Series => chance,
+            RatingMode::Maps => series_chance(chance, best_of / 2 + 1)
+        })
+    }
+
+    fn update(&mut self, _match: &MatchBriefInfo, names: [&str; 2], a: f64, b: f64) {
+        // Teams that haven't played yet start from the initial rating
+        let expected_a = expected(self.rating(names[0]), self.rating(names[1]));
+
+        // How many games the first team won more than expected
+        let surplus = match self.mode {
+            RatingMode::Series => {
+                let score = if a > b {
+                    1.0
+                } else if a < b {
+                    0.0
+                } else {
+                    0.5
+                };
+
+                score - expected_a
+            },
+            RatingMode::Maps => a - expected_a * (a + b)
+        };
+        let change = self.k_factor * surplus;
+
+        for (i, &change) in [change, -change].iter().enumerate() {
+            let initial_rating = self.initial_rating;
+            let team = self.teams.entry(team_key(names[i])).or_insert_with(|| TeamRating {
+                team: names[i].trim().into(),
+                rating: initial_rating,
+                history: vec![]
+            });
+
+            team.rating += change;
+            team.history.push(RatingChange {
+                time: _match.scheduled_time,
+                opponent: names[1 - i].trim().into(),
+                change,
+                rating: team.rating
+            });
+        }
+    }
+}
+
+/// The chance that a team rated `a` beats a team rated `b`.
+fn expected(a: f64, b: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((b - a) / 400.0))
+}
+
+/// The chance that a team that wins each game with the given chance is the
+/// first to win `wins` games.
+fn series_chance(chance: f64, wins: u32) -> f64 {
+    // Sum over how many games the opponent wins first, times the ways those
+    // games can be arranged before the team's last win
+    let mut ways = 1.0;
+    let mut total = 0.0;
+
+    for losses in 0..wins {
+        if losses > 0 {
+            ways *= f64::from(wins - 1 + losses) / f64::from(losses);
+        }
+
+        total += ways * chance.powi(wins as i32) * (1.0 - chance).powi(losses as i32);
+    }
+
+    total
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::known_team;
+    use crate::data_structs::TeamSlot;
+    use chrono::TimeZone;
+
+    fn played(a: (&str, u8), b: (&str, u8)) -> MatchBriefInfo {
+        MatchBriefInfo {
+            teams: [
+                known_team(a.0, Some(a.1)),
+                known_team(b.0, Some(b.1))
+            ],
+            ..MatchBriefInfo::default()
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn series_ratings() {
+        let mut elo = Elo::new();
+        elo.ingest(&[played(("Alpha", 3), ("Bravo", 0))]);
+
+        // Evenly matched teams move by half the K-factor
+        assert!(close(elo.rating("Alpha"), 1516.0));
+        assert!(close(elo.rating("bravo"), 1484.0));
+        assert!(close(elo.rating("Charlie"), 1500.0));
+
+        let mut undecided = played(("Alpha", 3), ("TBD", 0));
+        undecided.teams[1].slot = TeamSlot::Tbd;
+
+        elo.ingest(&[
+            played(("Alpha", 3), ("Bravo", 2)),
+            played(("Charlie", 2), ("Alpha", 2)),
+            // Not played yet
+            MatchBriefInfo::default(),
+            // The opponent isn't known
+            undecided
+        ]);
+
+        let history: Vec<&str> = elo.team("Alpha").unwrap().history.iter().map(|c| c.opponent.as_str()).collect();
+        assert_eq!(history, vec!["Bravo", "Bravo", "Charlie"]);
+
+        // The favorite loses rating in a draw
+        let draw = elo.team("Alpha").unwrap().history[2].change;
+        assert!(draw < 0.0);
+        assert!(close(elo.team("Charlie").unwrap().history[0].change, -draw));
+
+        let rankings: Vec<&str> = elo.rankings().iter().map(|team| team.team.as_str()).collect();
+        assert_eq!(rankings, vec!["Alpha", "Charlie", "Bravo"]);
+
+        // Ratings always add up to what they started with
+        let total: f64 = elo.rankings().iter().map(|team| team.rating).sum();
+        assert!(close(total, 4500.0));
+    }
+
+    #[test]
+    fn map_ratings() {
+        let mut series = Elo::new().k_factor(10.0).initial_rating(1000.0);
+        let mut maps = Elo::new().k_factor(10.0).initial_rating(1000.0).mode(RatingMode::Maps);
+
+        let sweep = [played(("Alpha", 3), ("Bravo", 0))];
+        series.ingest(&sweep);
+        maps.ingest(&sweep);
+
+        assert!(close(series.rating("Alpha"), 1005.0));
+        assert!(close(maps.rating("Alpha"), 1015.0));
+
+        let mut close_series = Elo::new().mode(RatingMode::Maps);
+        close_series.ingest(&[played(("Alpha", 3), ("Bravo", 2))]);
+        assert!(close(close_series.rating("Alpha"), 1516.0));
+    }
+
+    #[test]
+    fn predictions() {
+        let mut elo = Elo::new();
+        let upcoming = MatchBriefInfo {
+            teams: [
+                known_team("Alpha", None),
+                known_team("Bravo", None)
+            ],
+            ..MatchBriefInfo::default()
+        };
+
+        // Neither team has played
+        assert_eq!(elo.predict(&upcoming), None);
+
+        elo.ingest(&[played(("Alpha", 3), ("Bravo", 1)), played(("Bravo", 0), ("Alpha", 3))]);
+        let chance = elo.predict(&upcoming).unwrap();
+        assert!(chance > 0.5 && chance < 1.0);
+        assert_eq!(elo.predict_series(&upcoming, 5), Some(chance));
+
+        let mut tbd = upcoming.clone();
+        tbd.teams[1].slot = TeamSlot::Tbd;
+        assert_eq!(elo.predict(&tbd), None);
+
+        // 400 points is ten to one
+        assert!(close(expected(1900.0, 1500.0), 10.0 / 11.0));
+    }
+
+    #[test]
+    fn series_predictions() {
+        let mut elo = Elo::new().mode(RatingMode::Maps);
+        elo.ingest(&[played(("Alpha", 3), ("Bravo", 0))]);
+        let upcoming = played(("Alpha", 0), ("Bravo", 0));
+
+        // The better team is more likely to win a longer series
+        let map = elo.predict(&upcoming).unwrap();
+        let bo3 = elo.predict_series(&upcoming, 3).unwrap();
+        let bo5 = elo.predict_series(&upcoming, 5).unwrap();
+        assert!(map > 0.5 && map < bo3 && bo3 < bo5 && bo5 < 1.0);
+        assert!(close(elo.predict_series(&upcoming, 1).unwrap(), map));
+
+        assert!(close(series_chance(0.5, 3), 0.5));
+        assert!(close(series_chance(0.6, 2), 0.6 * 0.6 + 2.0 * 0.6 * 0.6 * 0.4));
+    }
+
+    #[test]
+    fn orders_matches_by_time() {
+        let at = |_match: MatchBriefInfo, ts: i64| MatchBriefInfo {
+            scheduled_time: Some(Utc.timestamp_opt(ts, 0).unwrap()),
+            .._match
+        };
+        let first = at(played(("Alpha", 3), ("Bravo", 0)), 100);
+        let second = at(played(("Bravo", 3), ("Charlie", 0)), 200);
+
+        let mut in_order = Elo::new();
+        in_order.ingest(&[first.clone(), second.clone()]);
+        let mut out_of_order = Elo::new();
+        out_of_order.ingest(&[second, first]);
+
+        assert_eq!(in_order.team("Bravo"), out_of_order.team("Bravo"));
+        assert_eq!(out_of_order.team("Bravo").unwrap().history[0].opponent, "Alpha");
+    }
+}
@@ -0,0 +1,160 @@
+//! Handles extraction of content from event pages
+//! (e.g. https://www.over.gg/event/123/example-cup).
+
+use super::{load_sels, Extracted, Issues};
+use super::links::SiteLink;
+use super::match_list::extract_match_list;
+use scraper::Html;
+use crate::error::*;
+use crate::fetch::Page;
+use crate::site::Site;
+use crate::data_structs::{EventId, EventPageInfo};
+
+const EVENT_PAGE_SELECTORS_STR: &str = include_str!("../../selectors/event_page.toml");
+
+/// Handles extraction of content from an event page.
+///
+/// You can either provide your own HTML string (see the `From<String>` impl)
+/// or use the `http_client::Downloader`. Links in the HTML are resolved
+/// against the live site unless you say otherwise with `with_site`.
+///
+/// Like `MainPageScraper`, every method comes in a lenient, a
+/// `_with_warnings` and a `_strict` flavor.
+pub struct EventPageScraper {
+    doc: Html,
+    site: Site,
+    id: Option<EventId>,
+    url: Option<String>
+}
+
+impl From<String> for EventPageScraper {
+    fn from(html: String) -> Self {
+        EventPageScraper {
+            doc: Html::parse_document(&html),
+            site: Site::default(),
+            id: None,
+            url: None
+        }
+    }
+}
+
+impl From<Page> for EventPageScraper {
+    /// The event's ID is taken from the page's URL.
+    fn from(page: Page) -> Self {
+        EventPageScraper {
+            url: Some(page.url),
+            ..EventPageScraper::from(page.body)
+        }
+    }
+}
+
+impl EventPageScraper {
+    /// Resolve links in the HTML against the given site, which should be the
+    /// one the HTML came from.
+    #[inline]
+    pub fn with_site(mut self, site: Site) -> Self {
+        self.site = site;
+        self
+    }
+
+    /// Set the ID of the event the page is for.
+    ///
+    /// The page itself doesn't say, so this is needed for `EventPageInfo::id`
+    /// unless the scraper was made from a `Page` fetched from the event's URL.
+    #[inline]
+    pub fn with_id(mut self, id: EventId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Gets the event's name and the matches listed on its page.
+    #[inline]
+    pub fn event_info(&self) -> EventPageInfo {
+        self.event_info_with_warnings().into_value()
+    }
+
+    /// Like `event_info`, but also reports anything that could not be
+    /// extracted.
+    #[inline]
+    pub fn event_info_with_warnings(&self) -> Extracted<EventPageInfo> {
+        let mut issues = Issues::default();
+        let info = self.extract_event_info(&mut issues);
+
+        issues.finish(info)
+    }
+
+    /// Like `event_info`, but fails if any of the expected information could
+    /// not be extracted.
+    #[inline]
+    pub fn event_info_strict(&self) -> Result<EventPageInfo> {
+        self.event_info_with_warnings().into_strict()
+    }
+
+    fn extract_event_info(&self, issues: &mut Issues) -> EventPageInfo {
+        let mut info = EventPageInfo::default();
+        let selectors = load_sels(EVENT_PAGE_SELECTORS_STR);
+        let root = self.doc.root_element();
+
+        info.id = self.id.or_else(|| match self.url.as_ref().and_then(|url| self.site.link(url)) {
+            Some(SiteLink::Event(id)) => Some(id),
+            _ => None
+        });
+
+        // Event name
+        if let Some(elem) = root.select(&selectors["name"]).next() {
+            info.name = elem.text().collect::<String>().trim().into();
+        } else {
+            issues.missing("name", selectors.source("name"));
+        }
+
+        info.matches = extract_match_list(root, &self.site, "matches", issues);
+
+        info
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data_structs::{MatchId, TeamSlot};
+
+    #[test]
+    fn event_info() {
+        let scraper = EventPageScraper::from(String::from(r#"
+            <h1 class="wf-title">Example Cup</h1>
+            <a class="m-item" href="/1001/alpha-vs-bravo">
+                <div class="m-item-event">Example Cup</div>
+                <div class="m-item-series">Grand Final</div>
+                <div class="m-item-team"><span class="m-item-team-name">Alpha</span><span class="m-item-team-score">3</span></div>
+                <div class="m-item-team"><span class="m-item-team-name">Bravo</span><span class="m-item-team-score">1</span></div>
+                <div class="m-item-date" data-utc-ts="1533600000"></div>
+            </a>
+            <a class="m-item" href="/1004/tbd-vs-tbd">
+                <div class="m-item-event">Example Cup</div>
+                <div class="m-item-series">Third Place Match</div>
+                <div class="m-item-team"><span class="m-item-team-name">Loser of #1002</span></div>
+                <div class="m-item-team"><span class="m-item-team-name">Loser of #1003</span></div>
+                <div class="m-item-date" data-utc-ts="1533700000"></div>
+            </a>
+        "#)).with_id(EventId(10));
+        let info = scraper.event_info_strict().unwrap();
+
+        assert_eq!(info.id, Some(EventId(10)));
+        assert_eq!(info.name, "Example Cup");
+        assert_eq!(info.matches.len(), 2);
+        assert_eq!(info.matches[0].teams[0].maps_won, Some(3));
+        assert_eq!(info.matches[1].id, Some(MatchId(1004)));
+        assert_eq!(info.matches[1].teams[0].slot, TeamSlot::LoserOf(MatchId(1002)));
+        assert_eq!(info.matches[1].teams[1].maps_won, None);
+    }
+
+    #[test]
+    fn missing_name() {
+        let scraper = EventPageScraper::from(String::from("<p>Not an event page</p>"));
+        let extracted = scraper.event_info_with_warnings();
+
+        assert_eq!(extracted.value, EventPageInfo::default());
+        assert_eq!(extracted.warnings.len(), 1);
+        assert_eq!(extracted.warnings[0].path, "name");
+    }
+}
@@ -0,0 +1,147 @@
+//! Finds links to match, team, event and player pages in any page's HTML.
+
+use crate::data_structs::{MatchId, TeamId, EventId, PlayerId};
+use crate::site::Site;
+use scraper::{Html, Selector};
+use url::Url;
+
+/// A link to one of the kinds of page on the site that has an ID.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone)]
+pub enum SiteLink {
+    Match(MatchId),
+    Team(TeamId),
+    Event(EventId),
+    Player(PlayerId)
+}
+
+impl SiteLink {
+    /// Works out what a link to the given path points at.
+    ///
+    /// Match pages live at `/12345/...`, while the others live at
+    /// `/team/123/...`, `/event/123/...` and `/player/123/...`.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let mut segments = path.trim().trim_start_matches('/').split('/');
+        let first = segments.next()?;
+
+        if let Ok(id) = first.parse() {
+            return Some(SiteLink::Match(MatchId(id)));
+        }
+
+        let id = segments.next()?.parse().ok()?;
+
+        match first {
+            "team" => Some(SiteLink::Team(TeamId(id))),
+            "event" => Some(SiteLink::Event(EventId(id))),
+            "player" => Some(SiteLink::Player(PlayerId(id))),
+            _ => None
+        }
+    }
+
+    /// A short string that uniquely identifies the linked page, such as
+    /// `match/12345`.
+    pub fn key(&self) -> String {
+        match *self {
+            SiteLink::Match(id) => format!("match/{}", id),
+            SiteLink::Team(id) => format!("team/{}", id),
+            SiteLink::Event(id) => format!("event/{}", id),
+            SiteLink::Player(id) => format!("player/{}", id)
+        }
+    }
+}
+
+/// Finds every link in the given HTML that points at a match, team, event or
+/// player page on the given site.
+///
+/// Links are resolved and classified with `Site::resolve` and `Site::link`,
+/// so only links to the site's origin (and below its base path) count. Each
+/// linked page is only returned once, in the order it first appears.
+pub fn site_links(html: &str, site: &Site) -> Vec<(Url, SiteLink)> {
+    let doc = Html::parse_document(html);
+    let anchors = Selector::parse("a[href]").unwrap();
+    let mut links: Vec<(Url, SiteLink)> = vec![];
+
+    for href in doc.select(&anchors).filter_map(|anchor| anchor.value().attr("href")) {
+        let url = match site.resolve(href) {
+            Some(url) => url,
+            None => continue
+        };
+
+        if let Some(link) = site.link(href) {
+            if !links.iter().any(|&(_, seen)| seen == link) {
+                links.push((url, link));
+            }
+        }
+    }
+
+    links
+}
+
+/// Finds every link in the given HTML that points at a page of the given
+/// site's results archive, with the number of the page.
+///
+/// Each page is only returned once, in the order it first appears, as the
+/// URL `Site::results_page` gives for it.
+pub fn results_page_links(html: &str, site: &Site) -> Vec<(Url, u32)> {
+    let doc = Html::parse_document(html);
+    let anchors = Selector::parse("a[href]").unwrap();
+    let mut pages: Vec<(Url, u32)> = vec![];
+
+    for href in doc.select(&anchors).filter_map(|anchor| anchor.value().attr("href")) {
+        if let Some(page) = site.results_page_number(href) {
+            if !pages.iter().any(|&(_, seen)| seen == page) {
+                pages.push((site.results_page(page), page));
+            }
+        }
+    }
+
+    pages
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_site_links() {
+        let html = r#"
+            <a href="/12345/seoul-dynasty-vs-london-spitfire">match</a>
+            <a href="https://www.over.gg/team/7/seoul-dynasty">team</a>
+            <a href="/event/42/overwatch-league">event</a>
+            <a href="/player/3/fleta">player</a>
+            <a href="/12345/again">duplicate</a>
+            <a href="/forums">forums</a>
+            <a href="https://www.twitch.tv/123">elsewhere</a>
+            <a href="http://www.over.gg/12346/other-scheme">elsewhere</a>
+            <a href="https://www.over.gg:8443/12347/other-port">elsewhere</a>
+        "#;
+        let links: Vec<SiteLink> = site_links(html, &Site::default()).into_iter().map(|(_, l)| l).collect();
+
+        assert_eq!(links, vec![
+            SiteLink::Match(MatchId(12345)),
+            SiteLink::Team(TeamId(7)),
+            SiteLink::Event(EventId(42)),
+            SiteLink::Player(PlayerId(3))
+        ]);
+    }
+
+    #[test]
+    fn finds_results_page_links() {
+        let html = r#"
+            <a href="/matches/results?page=2">2</a>
+            <a href="/matches/results?page=3">3</a>
+            <a href="/matches/results?page=2">Next</a>
+            <a href="https://www.over.gg/matches/results">1</a>
+            <a href="/matches">upcoming</a>
+            <a href="/12345/a-vs-b">match</a>
+        "#;
+        let pages: Vec<(String, u32)> = results_page_links(html, &Site::default()).into_iter()
+            .map(|(url, page)| (url.into(), page))
+            .collect();
+
+        assert_eq!(pages, vec![
+            ("https://www.over.gg/matches/results?page=2".into(), 2),
+            ("https://www.over.gg/matches/results?page=3".into(), 3),
+            ("https://www.over.gg/matches/results".into(), 1)
+        ]);
+    }
+}
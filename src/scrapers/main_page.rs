@@ -1,49 +1,143 @@
 //! Handles extraction of content from the main page (https://www.over.gg/).
 
-use super::load_sels;
-use scraper::Html;
-use chrono::{Utc, TimeZone, LocalResult};
+use super::{load_sels, parse_countdown, team_slot, Countdown, Extracted, Issues, Selectors, SCORE_PLACEHOLDERS};
+use super::links::SiteLink;
+use scraper::{ElementRef, Html};
+use chrono::{DateTime, Utc, TimeZone, LocalResult};
+use crate::error::*;
+use crate::fetch::Page;
+use crate::site::Site;
 use crate::data_structs::{
     MatchBriefInfo,
     MatchBriefType,
+    TimeSource,
     Livestreams,
     LivestreamInfo
 };
 use crate::data_structs::MatchBriefType::*;
 
-const MATCHES_BRIEF_SELECTORS_STR: &'static str = include_str!("../../selectors/matches_brief.toml");
-const LIVE_STREAMS_SELECTORS_STR: &'static str = include_str!("../../selectors/live_streams.toml");
+const MATCHES_BRIEF_SELECTORS_STR: &str = include_str!("../../selectors/matches_brief.toml");
+const LIVE_STREAMS_SELECTORS_STR: &str = include_str!("../../selectors/live_streams.toml");
 
 /// Handles extraction of content from the main page (https://www.over.gg/).
 ///
 /// You can either provide your own HTML string (see the `From<String>` impl)
-/// or use the `http_client::Downloader`.
+/// or use the `http_client::Downloader`. Links in the HTML are resolved
+/// against the live site unless you say otherwise with `with_site`.
+///
+/// Every method comes in three flavors: a lenient one that falls back to
+/// default values for anything it can't extract, a `_with_warnings` one that
+/// does the same but also tells you what it couldn't extract, and a `_strict`
+/// one that returns an `Error::Extraction` describing the first thing it
+/// couldn't extract.
 pub struct MainPageScraper {
-    doc: Html
+    doc: Html,
+    site: Site,
+    fetched_at: Option<DateTime<Utc>>
 }
 
 impl From<String> for MainPageScraper {
     fn from(html: String) -> Self {
         MainPageScraper {
-            doc: Html::parse_document(&html)
+            doc: Html::parse_document(&html),
+            site: Site::default(),
+            fetched_at: None
         }
     }
 }
 
+/// Uses the page's fetch time (see `with_fetched_at`).
+impl From<Page> for MainPageScraper {
+    fn from(page: Page) -> Self {
+        MainPageScraper::from(page.body).with_fetched_at(page.fetched_at)
+    }
+}
+
 impl MainPageScraper {
+    /// Resolve links in the HTML against the given site, which should be the
+    /// one the HTML came from.
+    #[inline]
+    pub fn with_site(mut self, site: Site) -> Self {
+        self.site = site;
+        self
+    }
+
+    /// Set when the HTML was fetched.
+    ///
+    /// Matches whose exact time isn't given get one worked out from their
+    /// countdown (e.g. `2h 30m` or `LIVE`) relative to this instead, as
+    /// recorded in `MatchBriefInfo::time_source`.
+    #[inline]
+    pub fn with_fetched_at(mut self, fetched_at: DateTime<Utc>) -> Self {
+        self.fetched_at = Some(fetched_at);
+        self
+    }
+
     /// Gets information available on the main page for the matches of the given
     /// type.
     #[inline]
     pub fn matches_brief(&self, _type: MatchBriefType) -> Vec<MatchBriefInfo> {
+        self.matches_brief_with_warnings(_type).into_value()
+    }
+
+    /// Like `matches_brief`, but also reports anything that could not be
+    /// extracted.
+    #[inline]
+    pub fn matches_brief_with_warnings(&self, _type: MatchBriefType) -> Extracted<Vec<MatchBriefInfo>> {
+        let mut issues = Issues::default();
+        let matches_info = self.extract_matches_brief(_type, &mut issues);
+
+        issues.finish(matches_info)
+    }
+
+    /// Like `matches_brief`, but fails if any of the expected information
+    /// could not be extracted.
+    #[inline]
+    pub fn matches_brief_strict(&self, _type: MatchBriefType) -> Result<Vec<MatchBriefInfo>> {
+        self.matches_brief_with_warnings(_type).into_strict()
+    }
+
+    /**
+    Gets the livestream information available on the main page.
+    
+    This differs from simply grabbing OW livestreams from Twitch in that it
+    is a curated list consisting of pro players and tournament / event streams.
+    When there aren't enough of those specific streams live to fill the card,
+    the site fills out the remaining space with whatever the other top Overwatch
+    streams are on Twitch.
+    */
+    #[inline]
+    pub fn live_streams(&self) -> Livestreams {
+        self.live_streams_with_warnings().into_value()
+    }
+
+    /// Like `live_streams`, but also reports anything that could not be
+    /// extracted.
+    #[inline]
+    pub fn live_streams_with_warnings(&self) -> Extracted<Livestreams> {
+        let mut issues = Issues::default();
+        let live_streams = self.extract_live_streams(&mut issues);
+
+        issues.finish(live_streams)
+    }
+
+    /// Like `live_streams`, but fails if any of the expected information
+    /// could not be extracted.
+    #[inline]
+    pub fn live_streams_strict(&self) -> Result<Livestreams> {
+        self.live_streams_with_warnings().into_strict()
+    }
+
+    fn extract_matches_brief(&self, _type: MatchBriefType, issues: &mut Issues) -> Vec<MatchBriefInfo> {
         let mut matches_info = vec![];
         let selectors = load_sels(MATCHES_BRIEF_SELECTORS_STR);
-        let matches_selector = match _type {
-            MatchBriefType::Completed => &selectors["matches-completed"],
-            MatchBriefType::InFuture | MatchBriefType::Live => &selectors["matches-upcoming"]
+        let matches_key = match _type {
+            MatchBriefType::Completed => "matches-completed",
+            MatchBriefType::InFuture | MatchBriefType::Live => "matches-upcoming"
         };
 
         // Get the correct list of matches for the type we are looking for
-        if let Some(list) = self.doc.select(matches_selector).next() {
+        if let Some(list) = self.doc.select(&selectors[matches_key]).next() {
             // Now we get the match type we want
             let matches: Vec<::scraper::ElementRef> = 
                 list.select(&selectors["match"]).filter(|e| {
@@ -62,50 +156,114 @@ impl MainPageScraper {
                 }).collect();
 
             // Finally we get information for each match
-            for _match in matches {
+            for (index, _match) in matches.into_iter().enumerate() {
                 let mut match_info = MatchBriefInfo::default();
+                let path = format!("matches[{}]", index);
+
+                // Match ID, from links that look like `/12345/team-a-vs-team-b`
+                match _match.value().attr("href") {
+                    Some(val) => match self.site.link(val) {
+                        Some(SiteLink::Match(id)) => match_info.id = Some(id),
+                        _ => issues.unparseable(format!("{}.id", path), selectors.attr_source("match", "href"), val.trim())
+                    },
+                    None => issues.missing(format!("{}.id", path), selectors.attr_source("match", "href"))
+                }
 
                 // Event name
                 if let Some(elem) = _match.select(&selectors["event_name"]).next() {
                     match_info.event.name = elem.text().collect::<String>().trim().into();
+                } else {
+                    issues.missing(format!("{}.event.name", path), selectors.source("event_name"));
                 }
 
                 // Event series
                 if let Some(elem) = _match.select(&selectors["event_series"]).next() {
                     match_info.event.series = elem.text().collect::<String>().trim().into();
+                } else {
+                    issues.missing(format!("{}.event.series", path), selectors.source("event_series"));
                 }
 
                 let mut teams = _match.select(&selectors["teams"]);
 
                 for i in 0..2 {
+                    let team_path = format!("{}.teams[{}]", path, i);
+
                     if let Some(team) = teams.next() {
                         // Team name
-                        if let Some(elem) = team.select(&selectors["team_name"]).next() {
-                            match_info.teams[i].name = elem.text().collect::<String>().trim().into();
+                        let name = team.select(&selectors["team_name"]).next()
+                            .map(|elem| elem.text().collect::<String>().trim().to_string())
+                            .unwrap_or_default();
+
+                        if name.is_empty() {
+                            issues.missing(format!("{}.name", team_path), selectors.source("team_name"));
                         }
 
+                        match_info.teams[i].slot = team_slot(team, &name, &self.site);
+
                         // Team won maps count
                         if let Some(elem) = team.select(&selectors["team_score"]).next() {
-                            match_info.teams[i].maps_won = elem.text()
-                                                                .collect::<String>()
-                                                                .trim()
-                                                                .parse()
-                                                                .ok();
+                            let text = elem.text().collect::<String>();
+                            let text = text.trim();
+                            match_info.teams[i].maps_won = text.parse().ok();
+
+                            if match_info.teams[i].maps_won.is_none() && !SCORE_PLACEHOLDERS.contains(&text) {
+                                issues.unparseable(
+                                    format!("{}.maps_won", team_path),
+                                    selectors.source("team_score"),
+                                    text
+                                );
+                            }
+                        } else if _type != InFuture {
+                            issues.missing(format!("{}.maps_won", team_path), selectors.source("team_score"));
                         }
+                    } else {
+                        issues.missing(team_path, selectors.source("teams"));
                     }
                 }
 
                 // Scheduled match time
-                if_chain! {
-                    if let Some(elem) = _match.select(&selectors["match_scheduled_time"]).next();
-                    // TODO: Get attr keys in file
-                    if let Some(val) = elem.value().attr("data-utc-ts");
-                    if let Ok(timestamp) = val.trim().parse();
-                    if let LocalResult::Single(datetime) = Utc.timestamp_opt(timestamp, 0);
+                let time_path = format!("{}.scheduled_time", path);
+                // Problems with the timestamp only matter if the countdown
+                // can't stand in for it
+                let mut time_issues = Issues::default();
+
+                // TODO: Get attr keys in file
+                match _match.select(&selectors["match_scheduled_time"]).next() {
+                    Some(elem) => match elem.value().attr("data-utc-ts") {
+                        Some(val) => match val.trim().parse() {
+                            Ok(timestamp) => match Utc.timestamp_opt(timestamp, 0) {
+                                LocalResult::Single(datetime) => match_info.scheduled_time = Some(datetime),
+                                _ => time_issues.invalid_timestamp(
+                                    time_path,
+                                    selectors.attr_source("match_scheduled_time", "data-utc-ts"),
+                                    val.trim()
+                                )
+                            },
+                            Err(_) => time_issues.unparseable(
+                                time_path,
+                                selectors.attr_source("match_scheduled_time", "data-utc-ts"),
+                                val.trim()
+                            )
+                        },
+                        None => time_issues.missing(
+                            time_path,
+                            selectors.attr_source("match_scheduled_time", "data-utc-ts")
+                        )
+                    },
+                    // Only upcoming matches are guaranteed to show a time
+                    None => if _type == InFuture {
+                        time_issues.missing(time_path, selectors.source("match_scheduled_time"));
+                    }
+                }
 
-                then {
-                    match_info.scheduled_time = Some(datetime);
-                }}
+                if match_info.scheduled_time.is_some() {
+                    match_info.time_source = Some(TimeSource::Timestamp);
+                } else if let Some((time, source)) = self.time_from_countdown(_match, &selectors) {
+                    match_info.scheduled_time = time;
+                    match_info.time_source = Some(source);
+                } else {
+                    issues.list.extend(time_issues.list);
+                }
 
                 matches_info.push(match_info);
             }
@@ -114,31 +272,54 @@ impl MainPageScraper {
         matches_info
     }
 
-    /**
-    Gets the livestream information available on the main page.
-    
-    This differs from simply grabbing OW livestreams from Twitch in that it
-    is a curated list consisting of pro players and tournament / event streams.
-    When there aren't enough of those specific streams live to fill the card,
-    the site fills out the remaining space with whatever the other top Overwatch
-    streams are on Twitch.
-    */
-    #[inline]
-    pub fn live_streams(&self) -> Livestreams {
+    /// Works out a match's time from the countdown shown on its card.
+    ///
+    /// Cards of live matches don't say when the match started, so they only
+    /// give a source and no time.
+    fn time_from_countdown(
+        &self,
+        _match: ElementRef,
+        selectors: &Selectors
+    ) -> Option<(Option<DateTime<Utc>>, TimeSource)> {
+        let texts = _match.select(&selectors["eta"])
+            .chain(_match.select(&selectors["match_scheduled_time"]))
+            .map(|elem| elem.text().collect::<String>());
+
+        for text in texts {
+            match parse_countdown(&text) {
+                Some(Countdown::In(duration)) => {
+                    if let Some(time) = self.fetched_at.and_then(|at| at.checked_add_signed(duration)) {
+                        return Some((Some(time), TimeSource::Countdown));
+                    }
+                },
+                Some(Countdown::Live) => return Some((None, TimeSource::Live)),
+                None => {}
+            }
+        }
+
+        None
+    }
+
+    fn extract_live_streams(&self, issues: &mut Issues) -> Livestreams {
         let mut live_streams = Livestreams::default();
         let selectors = load_sels(LIVE_STREAMS_SELECTORS_STR);
 
         // Find the panel that contains the cards for streams
         if let Some(streams) = self.doc.select(&selectors["streams"]).next() {
             // Now we extract the info for each stream
-            for stream in streams.select(&selectors["stream_cards"]) {
+            for (index, stream) in streams.select(&selectors["stream_cards"]).enumerate() {
                 let mut stream_info = LivestreamInfo::default();
+                let path = format!("streams[{}]", index);
 
                 // Stream name
                 if let Some(elem) = stream.select(&selectors["stream_name"]).next() {
                     stream_info.name = elem.text().collect::<String>().trim().into();
                 }
 
+                if stream_info.name.is_empty() {
+                    issues.missing(format!("{}.name", path), selectors.source("stream_name"));
+                }
+
                 // Stream title
                 if let Some(val) = stream.value().attr("title") {
                     stream_info.title = Some(val.trim().into());
@@ -146,16 +327,33 @@ impl MainPageScraper {
 
                 // Stream viewer count
                 if let Some(elem) = stream.select(&selectors["stream_viewer_count"]).next() {
-                    stream_info.viewer_count = elem.text()
-                                                    .collect::<String>()
-                                                    .trim()
-                                                    .parse()
-                                                    .ok();
+                    let text = elem.text().collect::<String>();
+                    stream_info.viewer_count = text.trim().parse().ok();
+
+                    if stream_info.viewer_count.is_none() {
+                        issues.unparseable(
+                            format!("{}.viewer_count", path),
+                            selectors.source("stream_viewer_count"),
+                            text.trim()
+                        );
+                    }
+                } else {
+                    issues.missing(format!("{}.viewer_count", path), selectors.source("stream_viewer_count"));
                 }
 
-                // Stream URL
+                // Stream URL, which can be relative or redirect through the site
                 if let Some(val) = stream.value().attr("href") {
-                    stream_info.url = val.trim().into();
+                    stream_info.url = self.site.resolve_target(val);
+
+                    if stream_info.url.is_none() {
+                        issues.unparseable(
+                            format!("{}.url", path),
+                            selectors.attr_source("stream_cards", "href"),
+                            val.trim()
+                        );
+                    }
+                } else {
+                    issues.missing(format!("{}.url", path), selectors.attr_source("stream_cards", "href"));
                 }
 
                 // We determine which vector the stream belongs in based on
@@ -173,16 +371,27 @@ impl MainPageScraper {
 
 #[cfg(test)]
 #[cfg(not(feature = "test-local-data"))]
+#[allow(clippy::comparison_to_empty, clippy::nonminimal_bool)]
 mod test {
     use super::*;
+    #[cfg(feature = "test-live-site")]
     use crate::http_client::Downloader;
+    use crate::data_structs::{MatchId, StreamPlatform, TeamSlot};
+    use crate::scrapers::FieldIssueKind;
 
-    #[test]
-    fn completed_matches_brief() {
-        let dl = Downloader::new();
-        let scraper = dl.main_page().unwrap();
-        let matches = scraper.matches_brief(Completed);
+    /// A synthetic main page; see the comment at the top of the file.
+    const FIXTURE_MAIN_PAGE: &str = include_str!("../../tests/fixtures/main_page.html");
+
+    fn fixture() -> MainPageScraper {
+        MainPageScraper::from(String::from(FIXTURE_MAIN_PAGE))
+    }
+
+    #[cfg(feature = "test-live-site")]
+    fn live_site() -> MainPageScraper {
+        Downloader::new().main_page().unwrap()
+    }
 
+    fn check_completed_matches_brief(matches: Vec<MatchBriefInfo>) {
         for _match in matches {
             // Make sure we got distinct event info
             assert!(_match.event.name != "");
@@ -194,9 +403,9 @@ mod test {
             assert!(!_match.teams[1].maps_won.is_none());
 
             // Make sure we got distinct team names
-            assert!(_match.teams[0].name != "");
-            assert!(_match.teams[1].name != "");
-            assert!(_match.teams[0].name != _match.teams[1].name);
+            assert!(_match.teams[0].name() != "");
+            assert!(_match.teams[1].name() != "");
+            assert!(_match.teams[0].name() != _match.teams[1].name());
 
             // Make sure that the methods to determine winner / loser work correctly
             if let (Some(winner), Some(loser)) = (_match.winner(), _match.loser()) {
@@ -206,12 +415,7 @@ mod test {
         }
     }
 
-    #[test]
-    fn future_matches_brief() {
-        let dl = Downloader::new();
-        let scraper = dl.main_page().unwrap();
-        let matches = scraper.matches_brief(InFuture);
-
+    fn check_future_matches_brief(matches: Vec<MatchBriefInfo>) {
         for _match in matches {
             // Make sure we got distinct event info
             assert!(_match.event.name != "");
@@ -223,10 +427,13 @@ mod test {
             assert!(_match.teams[1].maps_won.is_none());
             
             // Make sure we got non-empty team names
-            // Distinctness is not always possible for upcoming matches as the
-            // teams could be listed as TBD
-            assert!(_match.teams[0].name != "");
-            assert!(_match.teams[1].name != "");
+            // Distinctness is only possible once both teams are known, as
+            // they could be listed as TBD
+            assert!(_match.teams[0].name() != "");
+            assert!(_match.teams[1].name() != "");
+            if _match.teams.iter().all(|team| team.slot.is_known()) {
+                assert!(_match.teams[0].name() != _match.teams[1].name());
+            }
 
             // Make sure that the methods to determine winner / loser work correctly
             assert!(_match.winner().is_none());
@@ -234,12 +441,7 @@ mod test {
         }
     }
 
-    #[test]
-    fn live_matches_brief() {
-        let dl = Downloader::new();
-        let scraper = dl.main_page().unwrap();
-        let matches = scraper.matches_brief(Live);
-
+    fn check_live_matches_brief(matches: Vec<MatchBriefInfo>) {
         for _match in matches {
             // Make sure we got distinct event info
             assert!(_match.event.name != "");
@@ -251,23 +453,234 @@ mod test {
             assert!(!_match.teams[1].maps_won.is_none());
 
             // Make sure we got distinct team names
-            assert!(_match.teams[0].name != "");
-            assert!(_match.teams[1].name != "");
-            assert!(_match.teams[0].name != _match.teams[1].name);
+            assert!(_match.teams[0].name() != "");
+            assert!(_match.teams[1].name() != "");
+            assert!(_match.teams[0].name() != _match.teams[1].name());
         }
     }
 
-    #[test]
-    fn live_streams() {
-        let dl = Downloader::new();
-        let scraper = dl.main_page().unwrap();
-        let streams = scraper.live_streams();
-
+    fn check_live_streams(streams: Livestreams) {
         for stream in streams.curated.iter().chain(streams.other_top.iter()) {
             // Make sure we got a name
             assert!(stream.name != "");
         }
     }
+
+    #[test]
+    fn completed_matches_brief() {
+        let matches = fixture().matches_brief_strict(Completed).unwrap();
+
+        let ids: Vec<_> = matches.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![Some(MatchId(998)), Some(MatchId(997)), Some(MatchId(996))]);
+        assert_eq!(matches[0].event.name, "Example League");
+        assert_eq!(matches[0].event.series, "Week 2");
+        assert_eq!(matches[0].winner().unwrap().name(), "Hotel");
+        assert_eq!(matches[0].loser().unwrap().name(), "Golf");
+        assert_eq!(matches[0].scheduled_time.unwrap().timestamp(), 1_533_513_600);
+        assert_eq!(matches[0].time_source, Some(TimeSource::Timestamp));
+        // A draw has no winner
+        assert!(matches[1].winner().is_none());
+        // Completed matches don't always show a time
+        assert_eq!(matches[2].scheduled_time, None);
+        assert_eq!(matches[2].time_source, None);
+
+        check_completed_matches_brief(matches);
+    }
+
+    #[test]
+    fn future_matches_brief() {
+        let matches = fixture().matches_brief_strict(InFuture).unwrap();
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].id, Some(MatchId(1003)));
+        assert_eq!(matches[0].teams[0].name(), "Echo");
+        assert_eq!(matches[0].scheduled_time.unwrap().timestamp(), 1_533_603_600);
+        assert_eq!(matches[1].teams[0].name(), "TBD");
+        assert_eq!(matches[1].teams[0].slot, TeamSlot::Tbd);
+        assert_eq!(matches[1].teams[1].name(), "TBD");
+        assert_eq!(matches[0].teams[1].slot, TeamSlot::Known("Foxtrot".into()));
+
+        check_future_matches_brief(matches);
+    }
+
+    #[test]
+    fn live_matches_brief() {
+        let matches = fixture().matches_brief_strict(Live).unwrap();
+
+        let ids: Vec<_> = matches.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![Some(MatchId(1001)), Some(MatchId(1002))]);
+        assert_eq!(matches[0].teams[0].maps_won, Some(2));
+        assert_eq!(matches[0].teams[1].maps_won, Some(1));
+        assert_eq!(matches[1].scheduled_time, None);
+
+        check_live_matches_brief(matches);
+    }
+
+    #[test]
+    fn live_streams() {
+        let streams = fixture().live_streams_strict().unwrap();
+
+        // Streams with flags are the curated ones
+        let curated: Vec<&str> = streams.curated.iter().map(|s| s.name.as_str()).collect();
+        let other_top: Vec<&str> = streams.other_top.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(curated, vec!["ExampleCaster", "Example League"]);
+        assert_eq!(other_top, vec!["SomeRandomPlayer", "AnotherPlayer"]);
+
+        assert_eq!(streams.curated[0].title, Some("Grand finals watch party".into()));
+        assert_eq!(streams.curated[0].viewer_count, Some(12345));
+        assert_eq!(streams.curated[0].url.as_ref().unwrap().as_str(), "https://www.twitch.tv/examplecaster");
+        assert_eq!(streams.curated[0].platform(), Some(StreamPlatform::Twitch));
+        assert_eq!(streams.curated[1].platform(), Some(StreamPlatform::YouTube));
+        assert_eq!(streams.curated[1].title, None);
+        assert_eq!(streams.curated[1].viewer_count, Some(678));
+        assert_eq!(streams.other_top[1].viewer_count, Some(0));
+
+        check_live_streams(streams);
+    }
+
+    #[test]
+    fn countdown_times() {
+        let fetched_at = Utc.timestamp_opt(1_533_600_000, 0).unwrap();
+        let scraper = fixture().with_fetched_at(fetched_at);
+
+        // Exact timestamps win over countdowns
+        let upcoming = scraper.matches_brief_strict(InFuture).unwrap();
+        assert_eq!(upcoming[0].scheduled_time.unwrap().timestamp(), 1_533_603_600);
+        assert_eq!(upcoming[0].time_source, Some(TimeSource::Timestamp));
+
+        // The fetch time isn't when a live match started
+        let live = scraper.matches_brief_strict(Live).unwrap();
+        assert_eq!(live[0].scheduled_time, None);
+        assert_eq!(live[0].time_source, Some(TimeSource::Live));
+
+        let scraper = MainPageScraper::from(String::from(r#"
+            <div class="js-home-matches-upcoming">
+                <a class="wf-module-item mod-match" href="/5">
+                    <div class="h-match-preview-event">Event</div>
+                    <div class="h-match-preview-series">Series</div>
+                    <div class="h-match-team"><div class="h-match-team-name">Team A</div></div>
+                    <div class="h-match-team"><div class="h-match-team-name">Team B</div></div>
+                    <div class="h-match-preview-time">in 2h 30m</div>
+                </a>
+            </div>
+        "#));
+
+        // Without a fetch time there's nothing to count from
+        match scraper.matches_brief_strict(InFuture) {
+            Err(Error::Extraction(issue)) => assert_eq!(issue.path, "matches[0].scheduled_time"),
+            other => panic!("expected an extraction error, got {:?}", other)
+        }
+
+        let upcoming = scraper.with_fetched_at(fetched_at).matches_brief_strict(InFuture).unwrap();
+        assert_eq!(upcoming[0].scheduled_time.unwrap().timestamp(), 1_533_600_000 + 150 * 60);
+        assert_eq!(upcoming[0].time_source, Some(TimeSource::Countdown));
+    }
+
+    #[test]
+    #[cfg(feature = "test-live-site")]
+    fn live_site_completed_matches_brief() {
+        check_completed_matches_brief(live_site().matches_brief(Completed));
+    }
+
+    #[test]
+    #[cfg(feature = "test-live-site")]
+    fn live_site_future_matches_brief() {
+        check_future_matches_brief(live_site().matches_brief(InFuture));
+    }
+
+    #[test]
+    #[cfg(feature = "test-live-site")]
+    fn live_site_live_matches_brief() {
+        check_live_matches_brief(live_site().matches_brief(Live));
+    }
+
+    #[test]
+    #[cfg(feature = "test-live-site")]
+    fn live_site_live_streams() {
+        check_live_streams(live_site().live_streams());
+    }
+
+    const BROKEN_CARDS: &str = r#"
+        <div class="js-home-matches-completed">
+            <a class="wf-module-item mod-match" href="/1">
+                <div class="h-match-preview-event">Event</div>
+                <div class="h-match-preview-series">Series</div>
+                <div class="h-match-team">
+                    <div class="h-match-team-name">Team A</div>
+                    <div class="h-match-team-score mod-count">3</div>
+                </div>
+                <div class="h-match-team">
+                    <div class="h-match-team-name">Team B</div>
+                    <div class="h-match-team-score mod-count">x</div>
+                </div>
+            </a>
+        </div>
+        <div class="js-home-streams">
+            <a class="wf-module-item mod-disc" href="https://www.twitch.tv/a">
+                <span class="stream-item-txt-name">a</span>
+                <div class="stream-item-count">12</div>
+            </a>
+        </div>
+    "#;
+
+    #[test]
+    fn strict_reports_field_path() {
+        let scraper = MainPageScraper::from(String::from(BROKEN_CARDS));
+
+        // The lenient version still gives us what it could get
+        let matches = scraper.matches_brief(Completed);
+        assert_eq!(matches[0].teams[0].maps_won, Some(3));
+        assert_eq!(matches[0].teams[1].maps_won, None);
+
+        match scraper.matches_brief_strict(Completed) {
+            Err(Error::Extraction(issue)) => {
+                assert_eq!(issue.path, "matches[0].teams[1].maps_won");
+                assert_eq!(issue.selector, "div.h-match-team-score.mod-count");
+                assert_eq!(issue.snippet, Some("x".into()));
+                assert_eq!(issue.kind, FieldIssueKind::Unparseable);
+            },
+            other => panic!("expected an extraction error, got {:?}", other)
+        }
+
+        assert!(scraper.live_streams_strict().is_ok());
+    }
+
+    #[test]
+    fn lenient_reports_warnings() {
+        let scraper = MainPageScraper::from(String::from(BROKEN_CARDS));
+        let extracted = scraper.matches_brief_with_warnings(Completed);
+
+        assert_eq!(extracted.value.len(), 1);
+        assert_eq!(extracted.value[0].id, Some(MatchId(1)));
+        assert_eq!(extracted.value[0].teams[0].name(), "Team A");
+
+        // No scheduled time is fine for a completed match, so we should only
+        // hear about the score
+        let paths: Vec<&str> = extracted.warnings.iter().map(|w| w.path.as_str()).collect();
+        assert_eq!(paths, vec!["matches[0].teams[1].maps_won"]);
+
+        assert!(scraper.live_streams_with_warnings().is_clean());
+    }
+
+    #[test]
+    fn bracket_dependencies() {
+        let scraper = MainPageScraper::from(String::from(r#"
+            <div class="js-home-matches-upcoming">
+                <a class="wf-module-item mod-match" href="/1005/tbd-vs-tbd">
+                    <div class="h-match-preview-event">Example Cup</div>
+                    <div class="h-match-preview-series">Final</div>
+                    <div class="h-match-team"><div class="h-match-team-name">Winner of #1001</div></div>
+                    <div class="h-match-team"><div class="h-match-team-name">Loser of #1002</div></div>
+                    <div class="h-match-preview-time" data-utc-ts="1533603600"></div>
+                </a>
+            </div>
+        "#));
+
+        let matches = scraper.matches_brief_strict(InFuture).unwrap();
+        assert_eq!(matches[0].teams[0].slot, TeamSlot::WinnerOf(MatchId(1001)));
+        assert_eq!(matches[0].teams[1].slot, TeamSlot::LoserOf(MatchId(1002)));
+        assert_eq!(matches[0].teams[0].name(), "Winner of #1001");
+    }
 }
 
 #[cfg(test)]
@@ -276,53 +689,40 @@ mod test_local_data {
     use super::*;
     use crate::test_utils::*;
 
-    const TEST_DATA_MAIN_PAGE: &'static str = include_str!("../../test_data/www.over.gg.html");
-    const COMPLETED_MATCHES_BRIEF_PATH: &'static str = "test_data/completed_matches_brief.json";
-    const FUTURE_MATCHES_BRIEF_PATH: &'static str = "test_data/future_matches_brief.json";
-    const LIVE_MATCHES_BRIEF_PATH: &'static str = "test_data/live_matches_brief.json";
-    const LIVE_STREAMS_PATH: &'static str = "test_data/live_streams.json";
+    const COMPLETED_MATCHES_BRIEF_PATH: &str = "test_data/completed_matches_brief.json";
+    const FUTURE_MATCHES_BRIEF_PATH: &str = "test_data/future_matches_brief.json";
+    const LIVE_MATCHES_BRIEF_PATH: &str = "test_data/live_matches_brief.json";
+    const LIVE_STREAMS_PATH: &str = "test_data/live_streams.json";
 
     #[test]
     fn completed_matches_brief() {
-        let scraper = MainPageScraper::from(String::from(TEST_DATA_MAIN_PAGE));
+        let scraper = MainPageScraper::from(main_page_html());
         let matches = scraper.matches_brief(Completed);
 
-        let data = write_matches(COMPLETED_MATCHES_BRIEF_PATH, matches).unwrap();
-        let loaded_data = SaveData::load(COMPLETED_MATCHES_BRIEF_PATH).unwrap();
-
-        assert_eq!(&data, &loaded_data);
+        check_expected(COMPLETED_MATCHES_BRIEF_PATH, &SaveData::new(matches));
     }
 
     #[test]
     fn future_matches_brief() {
-        let scraper = MainPageScraper::from(String::from(TEST_DATA_MAIN_PAGE));
+        let scraper = MainPageScraper::from(main_page_html());
         let matches = scraper.matches_brief(InFuture);
 
-        let data = write_matches(FUTURE_MATCHES_BRIEF_PATH, matches).unwrap();
-        let loaded_data = SaveData::load(FUTURE_MATCHES_BRIEF_PATH).unwrap();
-
-        assert_eq!(&data, &loaded_data);
+        check_expected(FUTURE_MATCHES_BRIEF_PATH, &SaveData::new(matches));
     }
 
     #[test]
     fn live_matches_brief() {
-        let scraper = MainPageScraper::from(String::from(TEST_DATA_MAIN_PAGE));
+        let scraper = MainPageScraper::from(main_page_html());
         let matches = scraper.matches_brief(Live);
 
-        let data = write_matches(LIVE_MATCHES_BRIEF_PATH, matches).unwrap();
-        let loaded_data = SaveData::load(LIVE_MATCHES_BRIEF_PATH).unwrap();
-
-        assert_eq!(&data, &loaded_data);
+        check_expected(LIVE_MATCHES_BRIEF_PATH, &SaveData::new(matches));
     }
 
     #[test]
     fn live_streams() {
-        let scraper = MainPageScraper::from(String::from(TEST_DATA_MAIN_PAGE));
+        let scraper = MainPageScraper::from(main_page_html());
         let streams = scraper.live_streams();
 
-        write_non_overwrite(LIVE_STREAMS_PATH, &streams).unwrap();
-        let loaded_data: Livestreams = load(LIVE_STREAMS_PATH).unwrap();
-
-        assert_eq!(&streams, &loaded_data);
+        check_expected(LIVE_STREAMS_PATH, &streams);
     }
 }
@@ -0,0 +1,113 @@
+//! Handles extraction of the lists of matches shown on team and event pages.
+
+use super::{load_sels, team_slot, Issues, SCORE_PLACEHOLDERS};
+use super::links::SiteLink;
+use chrono::{LocalResult, TimeZone, Utc};
+use scraper::ElementRef;
+use crate::data_structs::{MatchBriefInfo, TimeSource};
+use crate::site::Site;
+
+const MATCH_LIST_SELECTORS_STR: &str = include_str!("../../selectors/match_list.toml");
+
+/// Extracts every match listed under `root`, reporting issues under paths
+/// starting with `path` (e.g. `matches`).
+///
+/// Lists mix completed and upcoming matches, so a missing score is only an
+/// issue if something unparseable is shown in its place.
+pub(super) fn extract_match_list(root: ElementRef, site: &Site, path: &str, issues: &mut Issues) -> Vec<MatchBriefInfo> {
+    let selectors = load_sels(MATCH_LIST_SELECTORS_STR);
+    let mut matches = vec![];
+
+    for (index, _match) in root.select(&selectors["match"]).enumerate() {
+        let mut match_info = MatchBriefInfo::default();
+        let path = format!("{}[{}]", path, index);
+
+        // Match ID, from links that look like `/12345/team-a-vs-team-b`
+        match _match.value().attr("href") {
+            Some(val) => match site.link(val) {
+                Some(SiteLink::Match(id)) => match_info.id = Some(id),
+                _ => issues.unparseable(format!("{}.id", path), selectors.attr_source("match", "href"), val.trim())
+            },
+            None => issues.missing(format!("{}.id", path), selectors.attr_source("match", "href"))
+        }
+
+        // Event name
+        if let Some(elem) = _match.select(&selectors["event_name"]).next() {
+            match_info.event.name = elem.text().collect::<String>().trim().into();
+        } else {
+            issues.missing(format!("{}.event.name", path), selectors.source("event_name"));
+        }
+
+        // Event series
+        if let Some(elem) = _match.select(&selectors["event_series"]).next() {
+            match_info.event.series = elem.text().collect::<String>().trim().into();
+        } else {
+            issues.missing(format!("{}.event.series", path), selectors.source("event_series"));
+        }
+
+        let mut teams = _match.select(&selectors["teams"]);
+
+        for i in 0..2 {
+            let team_path = format!("{}.teams[{}]", path, i);
+
+            let team = match teams.next() {
+                Some(team) => team,
+                None => {
+                    issues.missing(team_path, selectors.source("teams"));
+                    continue;
+                }
+            };
+
+            // Team name
+            let name = team.select(&selectors["team_name"]).next()
+                .map(|elem| elem.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+
+            if name.is_empty() {
+                issues.missing(format!("{}.name", team_path), selectors.source("team_name"));
+            }
+
+            match_info.teams[i].slot = team_slot(team, &name, site);
+
+            // Team won maps count
+            if let Some(elem) = team.select(&selectors["team_score"]).next() {
+                let text = elem.text().collect::<String>();
+                let text = text.trim();
+                match_info.teams[i].maps_won = text.parse().ok();
+
+                if match_info.teams[i].maps_won.is_none() && !SCORE_PLACEHOLDERS.contains(&text) {
+                    issues.unparseable(format!("{}.maps_won", team_path), selectors.source("team_score"), text);
+                }
+            }
+        }
+
+        // Scheduled match time
+        let time_path = format!("{}.scheduled_time", path);
+
+        match _match.select(&selectors["scheduled_time"]).next().and_then(|elem| elem.value().attr("data-utc-ts")) {
+            Some(val) => match val.trim().parse() {
+                Ok(timestamp) => match Utc.timestamp_opt(timestamp, 0) {
+                    LocalResult::Single(datetime) => {
+                        match_info.scheduled_time = Some(datetime);
+                        match_info.time_source = Some(TimeSource::Timestamp);
+                    },
+                    _ => issues.invalid_timestamp(
+                        time_path,
+                        selectors.attr_source("scheduled_time", "data-utc-ts"),
+                        val.trim()
+                    )
+                },
+                Err(_) => issues.unparseable(
+                    time_path,
+                    selectors.attr_source("scheduled_time", "data-utc-ts"),
+                    val.trim()
+                )
+            },
+            None => issues.missing(time_path, selectors.attr_source("scheduled_time", "data-utc-ts"))
+        }
+
+        matches.push(match_info);
+    }
+
+    matches
+}
@@ -0,0 +1,388 @@
+//! Handles extraction of content from match pages
+//! (e.g. https://www.over.gg/12345/team-a-vs-team-b).
+
+use super::{load_sels, team_slot, Extracted, Issues, Selectors, SCORE_PLACEHOLDERS};
+use chrono::{LocalResult, TimeZone, Utc};
+use scraper::{ElementRef, Html};
+use crate::error::*;
+use crate::fetch::Page;
+use crate::site::Site;
+use crate::data_structs::{LiveMatchState, MapInfo, MapStatus, MatchId};
+use crate::scrapers::links::SiteLink;
+
+const MATCH_PAGE_SELECTORS_STR: &str = include_str!("../../selectors/match_page.toml");
+
+/// Handles extraction of content from a match page.
+///
+/// You can either provide your own HTML string (see the `From<String>` impl)
+/// or use the `http_client::Downloader`. Links in the HTML are resolved
+/// against the live site unless you say otherwise with `with_site`.
+///
+/// Like `MainPageScraper`, every method comes in a lenient, a
+/// `_with_warnings` and a `_strict` flavor.
+pub struct MatchPageScraper {
+    doc: Html,
+    site: Site,
+    id: Option<MatchId>,
+    url: Option<String>
+}
+
+impl From<String> for MatchPageScraper {
+    fn from(html: String) -> Self {
+        MatchPageScraper {
+            doc: Html::parse_document(&html),
+            site: Site::default(),
+            id: None,
+            url: None
+        }
+    }
+}
+
+impl From<Page> for MatchPageScraper {
+    /// The match's ID is taken from the page's URL.
+    fn from(page: Page) -> Self {
+        MatchPageScraper {
+            url: Some(page.url),
+            ..MatchPageScraper::from(page.body)
+        }
+    }
+}
+
+impl MatchPageScraper {
+    /// Resolve links in the HTML against the given site, which should be the
+    /// one the HTML came from.
+    #[inline]
+    pub fn with_site(mut self, site: Site) -> Self {
+        self.site = site;
+        self
+    }
+
+    /// Set the ID of the match the page is for.
+    ///
+    /// The page itself doesn't say, so this is needed for `LiveMatchState::id`
+    /// unless the scraper was made from a `Page` fetched from the match's URL.
+    #[inline]
+    pub fn with_id(mut self, id: MatchId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /**
+    Gets the state of the match: the series score, and the maps that have
+    been played, are being played and are still to be played.
+
+    The map being played (see `LiveMatchState::current_map`) has its score so
+    far. Pages of matches that aren't live work too; they just don't have a
+    map in progress.
+    */
+    #[inline]
+    pub fn live_state(&self) -> LiveMatchState {
+        self.live_state_with_warnings().into_value()
+    }
+
+    /// Like `live_state`, but also reports anything that could not be
+    /// extracted.
+    #[inline]
+    pub fn live_state_with_warnings(&self) -> Extracted<LiveMatchState> {
+        let mut issues = Issues::default();
+        let state = self.extract_live_state(&mut issues);
+
+        issues.finish(state)
+    }
+
+    /// Like `live_state`, but fails if any of the expected information could
+    /// not be extracted.
+    #[inline]
+    pub fn live_state_strict(&self) -> Result<LiveMatchState> {
+        self.live_state_with_warnings().into_strict()
+    }
+
+    fn extract_live_state(&self, issues: &mut Issues) -> LiveMatchState {
+        let mut state = LiveMatchState::default();
+        let selectors = load_sels(MATCH_PAGE_SELECTORS_STR);
+        let root = self.doc.root_element();
+
+        state.id = self.id.or_else(|| match self.url.as_ref().and_then(|url| self.site.link(url)) {
+            Some(SiteLink::Match(id)) => Some(id),
+            _ => None
+        });
+
+        // Event name
+        if let Some(elem) = root.select(&selectors["event_name"]).next() {
+            state.event.name = elem.text().collect::<String>().trim().into();
+        } else {
+            issues.missing("event.name", selectors.source("event_name"));
+        }
+
+        // Event series
+        if let Some(elem) = root.select(&selectors["event_series"]).next() {
+            state.event.series = elem.text().collect::<String>().trim().into();
+        } else {
+            issues.missing("event.series", selectors.source("event_series"));
+        }
+
+        // Scheduled match time
+        match root.select(&selectors["scheduled_time"]).next().and_then(|elem| elem.value().attr("data-utc-ts")) {
+            Some(val) => match val.trim().parse() {
+                Ok(timestamp) => match Utc.timestamp_opt(timestamp, 0) {
+                    LocalResult::Single(datetime) => state.scheduled_time = Some(datetime),
+                    _ => issues.invalid_timestamp(
+                        "scheduled_time",
+                        selectors.attr_source("scheduled_time", "data-utc-ts"),
+                        val.trim()
+                    )
+                },
+                Err(_) => issues.unparseable(
+                    "scheduled_time",
+                    selectors.attr_source("scheduled_time", "data-utc-ts"),
+                    val.trim()
+                )
+            },
+            None => issues.missing("scheduled_time", selectors.attr_source("scheduled_time", "data-utc-ts"))
+        }
+
+        let mut teams = root.select(&selectors["teams"]);
+        let mut scores = root.select(&selectors["team_score"]);
+
+        for i in 0..2 {
+            let team_path = format!("teams[{}]", i);
+
+            // Team name
+            match teams.next() {
+                Some(team) => {
+                    let name = team.select(&selectors["team_name"]).next()
+                        .map(|elem| elem.text().collect::<String>().trim().to_string())
+                        .unwrap_or_default();
+
+                    if name.is_empty() {
+                        issues.missing(format!("{}.name", team_path), selectors.source("team_name"));
+                    }
+
+                    state.teams[i].slot = team_slot(team, &name, &self.site);
+                },
+                None => issues.missing(team_path.clone(), selectors.source("teams"))
+            }
+
+            // Team won maps count, which sits between the two teams
+            state.teams[i].maps_won = parse_score(
+                scores.next(),
+                format!("{}.maps_won", team_path),
+                &selectors,
+                "team_score",
+                issues
+            );
+        }
+
+        state.is_live = root.select(&selectors["live"]).next().is_some();
+
+        for (index, map) in root.select(&selectors["maps"]).enumerate() {
+            let mut map_info = MapInfo::default();
+            let path = format!("maps[{}]", index);
+
+            // Map name
+            if let Some(elem) = map.select(&selectors["map_name"]).next() {
+                map_info.name = elem.text().collect::<String>().trim().into();
+            }
+
+            if map_info.name.is_empty() {
+                issues.missing(format!("{}.name", path), selectors.source("map_name"));
+            }
+
+            let is_live = selectors["map_live"].matches(&map);
+            let is_completed = selectors["map_completed"].matches(&map);
+            let mut scores = map.select(&selectors["map_score"]);
+
+            for t in 0..2 {
+                let score_path = format!("{}.scores[{}]", path, t);
+
+                match scores.next() {
+                    Some(elem) => map_info.scores[t] = parse_score(
+                        Some(elem),
+                        score_path,
+                        &selectors,
+                        "map_score",
+                        issues
+                    ),
+                    // Maps that haven't started don't always show a score
+                    None => if is_live || is_completed {
+                        issues.missing(score_path, selectors.source("map_score"));
+                    }
+                }
+            }
+
+            // Maps that haven't started can show scores too (e.g. `0`), so
+            // only maps marked as complete are
+            map_info.status = if is_live {
+                MapStatus::InProgress
+            } else if is_completed {
+                MapStatus::Completed
+            } else {
+                MapStatus::Upcoming
+            };
+
+            state.maps.push(map_info);
+        }
+
+        state
+    }
+}
+
+/// Parses a score, reporting it unless it's missing or one of the
+/// placeholders shown before play starts.
+fn parse_score(elem: Option<ElementRef>, path: String, selectors: &Selectors, key: &str, issues: &mut Issues) -> Option<u8> {
+    let elem = match elem {
+        Some(elem) => elem,
+        None => {
+            issues.missing(path, selectors.source(key));
+            return None;
+        }
+    };
+
+    let text = elem.text().collect::<String>();
+    let text = text.trim();
+    let score = text.parse().ok();
+
+    if score.is_none() && !SCORE_PLACEHOLDERS.contains(&text) {
+        issues.unparseable(path, selectors.source(key), text);
+    }
+
+    score
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data_structs::{Team, TeamSlot};
+    use crate::scrapers::FieldIssueKind;
+
+    /// A synthetic page for a live match; see the comment at the top of the
+    /// file.
+    const FIXTURE_LIVE_MATCH_PAGE: &str = include_str!("../../tests/fixtures/match_page_live.html");
+
+    fn fixture() -> MatchPageScraper {
+        MatchPageScraper::from(String::from(FIXTURE_LIVE_MATCH_PAGE))
+    }
+
+    #[test]
+    fn live_state() {
+        let state = fixture().live_state_strict().unwrap();
+
+        assert_eq!(state.event.name, "Example Cup");
+        assert_eq!(state.event.series, "Grand Final");
+        assert_eq!(state.scheduled_time, Some(Utc.timestamp_opt(1_533_600_000, 0).unwrap()));
+        assert_eq!(state.teams[0].name(), "Alpha");
+        assert_eq!(state.teams[0].maps_won, Some(2));
+        assert_eq!(state.teams[1].name(), "Bravo");
+        assert_eq!(state.teams[1].maps_won, Some(1));
+        assert_eq!(state.teams[1].slot, TeamSlot::Known("Bravo".into()));
+        assert!(state.is_live);
+
+        let statuses: Vec<MapStatus> = state.maps.iter().map(|map| map.status).collect();
+        assert_eq!(statuses, vec![
+            MapStatus::Completed,
+            MapStatus::Completed,
+            MapStatus::Completed,
+            MapStatus::InProgress,
+            MapStatus::Upcoming
+        ]);
+
+        let current = state.current_map().unwrap();
+        assert_eq!(current.name, "Watchpoint: Gibraltar");
+        assert_eq!(current.scores, [Some(1), Some(2)]);
+        assert_eq!(current.winner(), None);
+
+        let winners: Vec<Option<Team>> = state.completed_maps().map(MapInfo::winner).collect();
+        assert_eq!(winners, vec![Some(Team::Zero), Some(Team::One), Some(Team::Zero)]);
+
+        assert_eq!(state.maps[4].name, "TBD");
+        assert_eq!(state.maps[4].scores, [None, None]);
+    }
+
+    #[test]
+    fn match_ids() {
+        assert_eq!(fixture().live_state().id, None);
+        assert_eq!(fixture().with_id(MatchId(1001)).live_state().id, Some(MatchId(1001)));
+
+        let page = Page {
+            url: "http://127.0.0.1:8080/overgg/1001/alpha-vs-bravo".into(),
+            fetched_at: Utc::now(),
+            headers: vec![],
+            body: FIXTURE_LIVE_MATCH_PAGE.into()
+        };
+        let mirror = Site::new("http://127.0.0.1:8080/overgg/").unwrap();
+        assert_eq!(MatchPageScraper::from(page).with_site(mirror).live_state().id, Some(MatchId(1001)));
+    }
+
+    #[test]
+    fn finished_match() {
+        let html = FIXTURE_LIVE_MATCH_PAGE
+            .replace(r#"<div class="match-header-vs-note mod-live">live</div>"#, "")
+            .replace("game-map mod-live", "game-map mod-complete");
+        let state = MatchPageScraper::from(html).live_state_strict().unwrap();
+
+        assert!(!state.is_live);
+        assert_eq!(state.current_map(), None);
+        assert_eq!(state.completed_maps().count(), 4);
+    }
+
+    #[test]
+    fn maps_are_only_completed_when_marked() {
+        let html = FIXTURE_LIVE_MATCH_PAGE.replace(
+            r#"<span class="game-map-score">–</span>"#,
+            r#"<span class="game-map-score">0</span>"#
+        );
+        let state = MatchPageScraper::from(html).live_state_strict().unwrap();
+
+        assert_eq!(state.maps[4].scores, [Some(0), Some(0)]);
+        assert_eq!(state.maps[4].status, MapStatus::Upcoming);
+        assert_eq!(state.completed_maps().count(), 3);
+    }
+
+    #[test]
+    fn bracket_dependencies() {
+        let html = FIXTURE_LIVE_MATCH_PAGE.replace(
+            r#"<a class="match-header-link mod-2" href="/team/2/bravo">
+                <div class="match-header-link-name">Bravo</div>"#,
+            r#"<a class="match-header-link mod-2" href="/999/charlie-vs-delta">
+                <div class="match-header-link-name">Winner of Charlie vs. Delta</div>"#
+        );
+        let state = MatchPageScraper::from(html).live_state_strict().unwrap();
+
+        assert_eq!(state.teams[0].slot, TeamSlot::Known("Alpha".into()));
+        assert_eq!(state.teams[1].slot, TeamSlot::WinnerOf(MatchId(999)));
+    }
+
+    #[test]
+    fn reports_field_paths() {
+        let html = FIXTURE_LIVE_MATCH_PAGE.replacen(
+            r#"<span class="game-map-score">2</span>"#,
+            r#"<span class="game-map-score">two</span>"#,
+            2
+        );
+        let scraper = MatchPageScraper::from(html);
+
+        let extracted = scraper.live_state_with_warnings();
+        assert_eq!(extracted.value.maps[0].scores, [None, Some(0)]);
+        assert_eq!(extracted.value.maps[0].status, MapStatus::Completed);
+
+        let paths: Vec<&str> = extracted.warnings.iter().map(|w| w.path.as_str()).collect();
+        assert_eq!(paths, vec!["maps[0].scores[0]", "maps[1].scores[0]"]);
+
+        match scraper.live_state_strict() {
+            Err(Error::Extraction(issue)) => {
+                assert_eq!(issue.selector, "span.game-map-score");
+                assert_eq!(issue.snippet, Some("two".into()));
+                assert_eq!(issue.kind, FieldIssueKind::Unparseable);
+            },
+            other => panic!("expected an extraction error, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn empty_page() {
+        let extracted = MatchPageScraper::from(String::new()).live_state_with_warnings();
+
+        assert_eq!(extracted.value, LiveMatchState::default());
+        assert!(!extracted.is_clean());
+    }
+}
@@ -3,17 +3,336 @@
 // TODO: Figure out what needs to be done about backslashes in the output
 
 pub mod main_page;
+pub mod match_page;
+pub mod team_page;
+pub mod event_page;
+pub mod links;
+mod match_list;
 
-use scraper::Selector;
+use crate::data_structs::{MatchId, TeamSlot};
+use crate::error::*;
+use crate::site::Site;
+use chrono::Duration;
+use scraper::{ElementRef, Selector};
+use self::links::SiteLink;
 use std::collections::HashMap;
+use std::fmt;
+use std::ops::Index;
+
+/// Text the site shows in place of a score for matches and maps that haven't
+/// started.
+const SCORE_PLACEHOLDERS: &[&str] = &["", "-", "–"];
+
+/// Describes a piece of data that could not be extracted from a page.
+///
+/// The lenient scraper methods fall back to default values when they run into
+/// one of these (the `_with_warnings` variants hand them back to you as part
+/// of an `Extracted`), while the `_strict` variants turn the first one they
+/// run into into an error.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FieldIssue {
+    /// Where the data would have ended up, e.g. `matches[3].teams[1].maps_won`.
+    pub path: String,
+    /// The selector that was used to look for the data.
+    ///
+    /// Attributes are written as `selector[attr]`.
+    pub selector: String,
+    /// The text that could not be understood, if any was found.
+    pub snippet: Option<String>,
+    pub kind: FieldIssueKind
+}
+
+/// The different ways extracting a piece of data can go wrong.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum FieldIssueKind {
+    /// Nothing matched the selector.
+    Missing,
+    /// Something was found, but its text couldn't be parsed.
+    Unparseable,
+    /// A timestamp was parsed but doesn't represent a valid point in time.
+    InvalidTimestamp
+}
+
+impl fmt::Display for FieldIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            FieldIssueKind::Missing => write!(f, "{}: nothing matched", self.path)?,
+            FieldIssueKind::Unparseable => write!(f, "{}: could not parse", self.path)?,
+            FieldIssueKind::InvalidTimestamp => write!(f, "{}: invalid timestamp", self.path)?
+        }
+
+        if let Some(ref snippet) = self.snippet {
+            write!(f, " {:?}", snippet)?;
+        }
+
+        write!(f, " (selector `{}`)", self.selector)
+    }
+}
+
+/// Data obtained by a lenient scraper method, along with warnings about
+/// anything that couldn't be extracted along the way.
+///
+/// Useful for logging data quality issues without losing the partial data.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Extracted<T> {
+    pub value: T,
+    pub warnings: Vec<FieldIssue>
+}
+
+impl<T> Extracted<T> {
+    /// Whether everything was extracted without any warnings.
+    #[inline]
+    pub fn is_clean(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    /// Discards the warnings, leaving just the data.
+    #[inline]
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    /// Turns the first warning (if there is one) into an `Error::Extraction`.
+    #[inline]
+    pub fn into_strict(self) -> Result<T> {
+        match self.warnings.into_iter().next() {
+            Some(issue) => Err(Error::Extraction(issue)),
+            None => Ok(self.value)
+        }
+    }
+}
+
+/// Collects the issues encountered while extracting data from a page.
+#[derive(Debug, Default)]
+struct Issues {
+    list: Vec<FieldIssue>
+}
+
+impl Issues {
+    fn missing<P: Into<String>, S: Into<String>>(&mut self, path: P, selector: S) {
+        self.push(path, selector, None, FieldIssueKind::Missing);
+    }
+
+    fn unparseable<P, S, T>(&mut self, path: P, selector: S, snippet: T)
+        where P: Into<String>,
+              S: Into<String>,
+              T: Into<String> {
+
+        self.push(path, selector, Some(snippet.into()), FieldIssueKind::Unparseable);
+    }
+
+    fn invalid_timestamp<P, S, T>(&mut self, path: P, selector: S, snippet: T)
+        where P: Into<String>,
+              S: Into<String>,
+              T: Into<String> {
+
+        self.push(path, selector, Some(snippet.into()), FieldIssueKind::InvalidTimestamp);
+    }
+
+    fn push<P: Into<String>, S: Into<String>>(
+        &mut self,
+        path: P,
+        selector: S,
+        snippet: Option<String>,
+        kind: FieldIssueKind
+    ) {
+        self.list.push(FieldIssue {
+            path: path.into(),
+            selector: selector.into(),
+            snippet,
+            kind
+        });
+    }
+
+    fn finish<T>(self, value: T) -> Extracted<T> {
+        Extracted {
+            value,
+            warnings: self.list
+        }
+    }
+}
+
+/// Selectors loaded from one of the files in `selectors/`, keeping hold of
+/// their source text so that it can be reported in `FieldIssue`s.
+struct Selectors {
+    sels: HashMap<String, (String, Selector)>
+}
+
+impl Selectors {
+    /// The source text of the selector with the given key.
+    fn source(&self, key: &str) -> &str {
+        &self.sels[key].0
+    }
+
+    /// The source text of the selector with the given key along with the
+    /// given attribute.
+    fn attr_source(&self, key: &str, attr: &str) -> String {
+        format!("{}[{}]", self.source(key), attr)
+    }
+}
+
+impl<'a> Index<&'a str> for Selectors {
+    type Output = Selector;
+
+    fn index(&self, key: &'a str) -> &Selector {
+        &self.sels[key].1
+    }
+}
+
+/// What a countdown shown on the site says.
+#[derive(Debug, PartialEq)]
+enum Countdown {
+    /// How far away the time counted to is (negative for times in the past)
+    In(Duration),
+    Live
+}
+
+/// Parses countdown text such as `2h 30m`, `in 1d 4h`, `3h ago` or `LIVE`.
+fn parse_countdown(text: &str) -> Option<Countdown> {
+    let text = text.trim().to_lowercase();
+
+    if text == "live" {
+        return Some(Countdown::Live);
+    }
+
+    let (text, sign) = match text.strip_suffix("ago") {
+        Some(rest) => (rest.trim(), -1),
+        None => (text.as_str(), 1)
+    };
+    let text = text.strip_prefix("in ").unwrap_or(text);
+
+    let mut seconds: i64 = 0;
+    let mut chars = text.chars().filter(|c| !c.is_whitespace()).peekable();
+
+    while chars.peek().is_some() {
+        let number: String = ::std::iter::from_fn(|| chars.next_if(char::is_ascii_digit)).collect();
+        let unit: String = ::std::iter::from_fn(|| chars.next_if(|c| c.is_alphabetic())).collect();
+
+        let unit_seconds = match unit.as_str() {
+            "w" | "wk" | "wks" | "week" | "weeks" => 7 * 24 * 60 * 60,
+            "d" | "day" | "days" => 24 * 60 * 60,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60,
+            "s" | "sec" | "secs" | "second" | "seconds" => 1,
+            _ => return None
+        };
+
+        seconds = number.parse::<i64>().ok()?
+            .checked_mul(unit_seconds)?
+            .checked_add(seconds)?;
+    }
+
+    // `Duration` counts milliseconds, and panics past what they can hold
+    if text.is_empty() || seconds > i64::MAX / 1000 {
+        return None;
+    }
+
+    Some(Countdown::In(Duration::seconds(sign * seconds)))
+}
+
+/// Works out who fills a team's spot in a match from the element showing the
+/// team and the name it shows.
+///
+/// A team that comes from another match is shown as e.g. `Winner of ...`,
+/// along with a link to that match or its ID written as `#12345`. Other
+/// numbers in the text (as in `Winner of Semifinal 2`) aren't match IDs.
+fn team_slot(team: ElementRef, name: &str, site: &Site) -> TeamSlot {
+    let text = name.trim().to_lowercase();
+    let slot: fn(MatchId) -> TeamSlot = if text.starts_with("winner of") {
+        TeamSlot::WinnerOf
+    } else if text.starts_with("loser of") {
+        TeamSlot::LoserOf
+    } else {
+        return TeamSlot::from_name(name);
+    };
+
+    let linked = Some(team).into_iter()
+        .chain(team.descendants().filter_map(ElementRef::wrap))
+        .filter_map(|elem| elem.value().attr("href"))
+        .find_map(|href| match site.link(href) {
+            Some(SiteLink::Match(id)) => Some(id),
+            _ => None
+        });
+    let id = linked.or_else(|| {
+        text.split_whitespace()
+            .filter_map(|token| token.strip_prefix('#'))
+            .find(|digits| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|digits| digits.parse().ok())
+            .map(MatchId)
+    });
+
+    // Still not a known team, even if we can't tell which match it's from
+    id.map_or_else(|| TeamSlot::Placeholder(name.split_whitespace().collect::<Vec<_>>().join(" ")), slot)
+}
 
 fn sel<S: AsRef<str>>(sel: S) -> Selector {
     Selector::parse(sel.as_ref()).unwrap()
 }
 
-fn load_sels<S: AsRef<str>>(from: S) -> HashMap<String, Selector> {
+fn load_sels<S: AsRef<str>>(from: S) -> Selectors {
     let strings: HashMap<String, String> =
         ::toml::from_str(from.as_ref()).unwrap();
 
-    strings.into_iter().map(|(key, val)| (key, sel(val))).collect()
+    Selectors {
+        sels: strings.into_iter().map(|(key, val)| {
+            let selector = sel(&val);
+            (key, (val, selector))
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn countdowns() {
+        let hours = |h: i64, m: i64| Some(Countdown::In(Duration::hours(h) + Duration::minutes(m)));
+
+        assert_eq!(parse_countdown("2h 30m"), hours(2, 30));
+        assert_eq!(parse_countdown(" in 1d 4h "), hours(28, 0));
+        assert_eq!(parse_countdown("45 mins"), hours(0, 45));
+        assert_eq!(parse_countdown("3h ago"), hours(-3, 0));
+        assert_eq!(parse_countdown("LIVE"), Some(Countdown::Live));
+
+        assert_eq!(parse_countdown(""), None);
+        assert_eq!(parse_countdown("TBD"), None);
+        assert_eq!(parse_countdown("2x"), None);
+        assert_eq!(parse_countdown("h"), None);
+        assert_eq!(parse_countdown("99999999999999999999w"), None);
+        assert_eq!(parse_countdown("9999999999999999w"), None);
+    }
+
+    #[test]
+    fn team_slots() {
+        let site = Site::default();
+        let slot = |html: &str| {
+            let doc = ::scraper::Html::parse_fragment(html);
+            let team = doc.select(&sel("div.team")).next().unwrap();
+            let name: String = team.text().collect();
+
+            team_slot(team, &name, &site)
+        };
+
+        assert_eq!(slot(r#"<div class="team">Alpha</div>"#), TeamSlot::Known("Alpha".into()));
+        assert_eq!(slot(r#"<div class="team"> TBD </div>"#), TeamSlot::Tbd);
+        assert_eq!(
+            slot(r#"<div class="team">Winner of <a href="/12345/a-vs-b">Semifinal 1</a></div>"#),
+            TeamSlot::WinnerOf(MatchId(12345))
+        );
+        assert_eq!(slot(r#"<div class="team">Loser of #678</div>"#), TeamSlot::LoserOf(MatchId(678)));
+        assert_eq!(slot(r#"<div class="team">Loser of Match 678</div>"#), TeamSlot::Placeholder("Loser of Match 678".into()));
+        assert_eq!(slot(r#"<div class="team">Winner of Semifinal</div>"#), TeamSlot::Placeholder("Winner of Semifinal".into()));
+        assert_eq!(
+            slot(r#"<div class="team"> Winner of
+                Semifinal 2 </div>"#),
+            TeamSlot::Placeholder("Winner of Semifinal 2".into())
+        );
+        // Only links to matches count
+        assert_eq!(
+            slot(r#"<div class="team">Winner of <a href="/event/4/cup">Cup</a></div>"#),
+            TeamSlot::Placeholder("Winner of Cup".into())
+        );
+        assert_eq!(slot(r#"<div class="team">Winners Esports</div>"#), TeamSlot::Known("Winners Esports".into()));
+    }
 }
@@ -0,0 +1,224 @@
+//! Handles extraction of content from team pages
+//! (e.g. https://www.over.gg/team/123/team-a).
+
+use super::{load_sels, Extracted, Issues};
+use super::links::SiteLink;
+use super::match_list::extract_match_list;
+use scraper::Html;
+use crate::error::*;
+use crate::fetch::Page;
+use crate::site::Site;
+use crate::data_structs::{PlayerBriefInfo, TeamId, TeamPageInfo};
+
+const TEAM_PAGE_SELECTORS_STR: &str = include_str!("../../selectors/team_page.toml");
+
+/// Handles extraction of content from a team page.
+///
+/// You can either provide your own HTML string (see the `From<String>` impl)
+/// or use the `http_client::Downloader`. Links in the HTML are resolved
+/// against the live site unless you say otherwise with `with_site`.
+///
+/// Like `MainPageScraper`, every method comes in a lenient, a
+/// `_with_warnings` and a `_strict` flavor.
+pub struct TeamPageScraper {
+    doc: Html,
+    site: Site,
+    id: Option<TeamId>,
+    url: Option<String>
+}
+
+impl From<String> for TeamPageScraper {
+    fn from(html: String) -> Self {
+        TeamPageScraper {
+            doc: Html::parse_document(&html),
+            site: Site::default(),
+            id: None,
+            url: None
+        }
+    }
+}
+
+impl From<Page> for TeamPageScraper {
+    /// The team's ID is taken from the page's URL.
+    fn from(page: Page) -> Self {
+        TeamPageScraper {
+            url: Some(page.url),
+            ..TeamPageScraper::from(page.body)
+        }
+    }
+}
+
+impl TeamPageScraper {
+    /// Resolve links in the HTML against the given site, which should be the
+    /// one the HTML came from.
+    #[inline]
+    pub fn with_site(mut self, site: Site) -> Self {
+        self.site = site;
+        self
+    }
+
+    /// Set the ID of the team the page is for.
+    ///
+    /// The page itself doesn't say, so this is needed for `TeamPageInfo::id`
+    /// unless the scraper was made from a `Page` fetched from the team's URL.
+    #[inline]
+    pub fn with_id(mut self, id: TeamId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Gets the team's name, roster and the matches listed on its page.
+    #[inline]
+    pub fn team_info(&self) -> TeamPageInfo {
+        self.team_info_with_warnings().into_value()
+    }
+
+    /// Like `team_info`, but also reports anything that could not be
+    /// extracted.
+    #[inline]
+    pub fn team_info_with_warnings(&self) -> Extracted<TeamPageInfo> {
+        let mut issues = Issues::default();
+        let info = self.extract_team_info(&mut issues);
+
+        issues.finish(info)
+    }
+
+    /// Like `team_info`, but fails if any of the expected information could
+    /// not be extracted.
+    #[inline]
+    pub fn team_info_strict(&self) -> Result<TeamPageInfo> {
+        self.team_info_with_warnings().into_strict()
+    }
+
+    fn extract_team_info(&self, issues: &mut Issues) -> TeamPageInfo {
+        let mut info = TeamPageInfo::default();
+        let selectors = load_sels(TEAM_PAGE_SELECTORS_STR);
+        let root = self.doc.root_element();
+
+        info.id = self.id.or_else(|| match self.url.as_ref().and_then(|url| self.site.link(url)) {
+            Some(SiteLink::Team(id)) => Some(id),
+            _ => None
+        });
+
+        // Team name
+        if let Some(elem) = root.select(&selectors["name"]).next() {
+            info.name = elem.text().collect::<String>().trim().into();
+        } else {
+            issues.missing("name", selectors.source("name"));
+        }
+
+        // Roster
+        for (index, player) in root.select(&selectors["roster"]).enumerate() {
+            let path = format!("roster[{}]", index);
+            let mut player_info = PlayerBriefInfo::default();
+
+            match player.value().attr("href") {
+                Some(val) => match self.site.link(val) {
+                    Some(SiteLink::Player(id)) => player_info.id = Some(id),
+                    _ => issues.unparseable(format!("{}.id", path), selectors.attr_source("roster", "href"), val.trim())
+                },
+                None => issues.missing(format!("{}.id", path), selectors.attr_source("roster", "href"))
+            }
+
+            if let Some(elem) = player.select(&selectors["player_name"]).next() {
+                player_info.name = elem.text().collect::<String>().trim().into();
+            } else {
+                issues.missing(format!("{}.name", path), selectors.source("player_name"));
+            }
+
+            info.roster.push(player_info);
+        }
+
+        info.matches = extract_match_list(root, &self.site, "matches", issues);
+
+        info
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data_structs::{MatchId, PlayerId, TeamSlot};
+    use chrono::Utc;
+
+    const PAGE: &str = r#"
+        <h1 class="wf-title">Alpha</h1>
+        <a class="team-roster-item" href="/player/100/alphaone">
+            <div class="team-roster-item-alias">AlphaOne</div>
+        </a>
+        <a class="team-roster-item" href="/player/101/alphatwo">
+            <div class="team-roster-item-alias">AlphaTwo</div>
+        </a>
+        <a class="m-item" href="/1001/alpha-vs-bravo">
+            <div class="m-item-event">Example Cup</div>
+            <div class="m-item-series">Grand Final</div>
+            <div class="m-item-team"><span class="m-item-team-name">Alpha</span><span class="m-item-team-score">3</span></div>
+            <div class="m-item-team"><span class="m-item-team-name">Bravo</span><span class="m-item-team-score">1</span></div>
+            <div class="m-item-date" data-utc-ts="1533600000"></div>
+        </a>
+        <a class="m-item" href="/1005/alpha-vs-tbd">
+            <div class="m-item-event">Example Cup</div>
+            <div class="m-item-series">Showmatch</div>
+            <div class="m-item-team"><span class="m-item-team-name">Alpha</span><span class="m-item-team-score">–</span></div>
+            <div class="m-item-team"><span class="m-item-team-name">TBD</span><span class="m-item-team-score">–</span></div>
+            <div class="m-item-date" data-utc-ts="1533700000"></div>
+        </a>
+    "#;
+
+    #[test]
+    fn team_info() {
+        let scraper = TeamPageScraper::from(String::from(PAGE)).with_id(TeamId(1));
+        let info = scraper.team_info_strict().unwrap();
+
+        assert_eq!(info.id, Some(TeamId(1)));
+        assert_eq!(info.name, "Alpha");
+        assert_eq!(info.roster, vec![
+            PlayerBriefInfo { id: Some(PlayerId(100)), name: "AlphaOne".into() },
+            PlayerBriefInfo { id: Some(PlayerId(101)), name: "AlphaTwo".into() }
+        ]);
+
+        assert_eq!(info.matches.len(), 2);
+        assert_eq!(info.matches[0].id, Some(MatchId(1001)));
+        assert_eq!(info.matches[0].event.series, "Grand Final");
+        assert_eq!(info.matches[0].teams[1].slot, TeamSlot::Known("Bravo".into()));
+        assert_eq!(info.matches[0].winner().unwrap().name(), "Alpha");
+        assert_eq!(info.matches[1].teams[1].slot, TeamSlot::Tbd);
+        assert_eq!(info.matches[1].teams[0].maps_won, None);
+    }
+
+    #[test]
+    fn ids_from_urls() {
+        let page = Page {
+            url: "https://www.over.gg/team/7/alpha".into(),
+            fetched_at: Utc::now(),
+            headers: vec![],
+            body: PAGE.into()
+        };
+
+        assert_eq!(TeamPageScraper::from(page).team_info().id, Some(TeamId(7)));
+        assert_eq!(TeamPageScraper::from(String::from(PAGE)).team_info().id, None);
+    }
+
+    #[test]
+    fn missing_information() {
+        let scraper = TeamPageScraper::from(String::from(r#"
+            <a class="team-roster-item" href="/forums">Someone</a>
+            <a class="m-item" href="/1001/alpha-vs-bravo">
+                <div class="m-item-team"><span class="m-item-team-name">Alpha</span><span class="m-item-team-score">x</span></div>
+            </a>
+        "#));
+
+        let paths: Vec<String> = scraper.team_info_with_warnings().warnings.into_iter().map(|w| w.path).collect();
+        assert_eq!(paths, vec![
+            "name",
+            "roster[0].id",
+            "roster[0].name",
+            "matches[0].event.name",
+            "matches[0].event.series",
+            "matches[0].teams[0].maps_won",
+            "matches[0].teams[1]",
+            "matches[0].scheduled_time"
+        ]);
+        assert!(scraper.team_info_strict().is_err());
+    }
+}
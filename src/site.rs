@@ -0,0 +1,292 @@
+//! Builds the URLs of pages on the site.
+//!
+//! Everything defaults to the live site, but a `Site` can point at any other
+//! origin serving the same paths, such as a staging mirror, an archived copy
+//! or a local test server.
+
+use crate::data_structs::{MatchId, TeamId, EventId, PlayerId};
+use crate::error::*;
+use crate::scrapers::links::SiteLink;
+use url::Url;
+
+/// The live site.
+pub const DEFAULT_BASE_URL: &str = "https://www.over.gg/";
+
+/// Where the results archive lives under the base URL.
+const RESULTS_PATH: &str = "matches/results";
+
+/// The site's redirect pages, as their path under the base URL and the query
+/// parameter they carry their target in.
+const REDIRECTS: &[(&str, &str)] = &[("go", "url")];
+
+/// Where the site lives, and the URLs of its pages.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Site {
+    base: Url
+}
+
+impl Default for Site {
+    #[inline]
+    fn default() -> Self {
+        // The default base URL is known to be valid
+        Self::new(DEFAULT_BASE_URL).unwrap()
+    }
+}
+
+impl Site {
+    /// A site served from the given base URL.
+    ///
+    /// The site is assumed to live at the root of the base URL's path, so
+    /// `https://mirror.example/overgg` and `https://mirror.example/overgg/`
+    /// are the same. URLs that other URLs can't be relative to, such as
+    /// `mailto:` ones, are rejected.
+    pub fn new(base_url: &str) -> Result<Self> {
+        let mut base = Url::parse(base_url)?;
+
+        if base.cannot_be_a_base() {
+            return Err(Error::InvalidBaseUrl(base_url.into()));
+        }
+
+        if !base.path().ends_with('/') {
+            let path = format!("{}/", base.path());
+            base.set_path(&path);
+        }
+
+        Ok(Self {
+            base
+        })
+    }
+
+    #[inline]
+    pub fn base_url(&self) -> &Url {
+        &self.base
+    }
+
+    /// Resolves a link found on one of the site's pages, which may be
+    /// relative, against the base URL.
+    #[inline]
+    pub fn resolve(&self, href: &str) -> Option<Url> {
+        self.join(href.trim())
+    }
+
+    /// Like `resolve`, but links to one of the site's redirect pages (such as
+    /// `/go?url=...`) are followed to their target.
+    ///
+    /// Any other link is left alone, even if its query string happens to
+    /// contain a URL.
+    pub fn resolve_target(&self, href: &str) -> Option<Url> {
+        let url = self.resolve(href)?;
+
+        if url.origin() != self.base.origin() {
+            return Some(url);
+        }
+
+        let redirect = url.path().strip_prefix(self.base.path())
+            .and_then(|path| REDIRECTS.iter().find(|&&(redirect_path, _)| redirect_path == path));
+        let param = match redirect {
+            Some(&(_, param)) => param,
+            None => return Some(url)
+        };
+
+        let target = url.query_pairs()
+            .filter(|(name, _)| name == param)
+            .filter_map(|(_, value)| Url::parse(&value).ok())
+            .find(|target| {
+                (target.scheme() == "http" || target.scheme() == "https")
+                    && target.origin() != self.base.origin()
+            });
+
+        Some(target.unwrap_or(url))
+    }
+
+    /// Works out what kind of page the given link (which may be relative)
+    /// points at, if it points at this site.
+    ///
+    /// Links to the same origin but outside the base URL's path aren't part
+    /// of the site.
+    pub fn link(&self, href: &str) -> Option<SiteLink> {
+        let url = self.resolve(href)?;
+
+        if url.origin() != self.base.origin() {
+            return None;
+        }
+
+        SiteLink::from_path(url.path().strip_prefix(self.base.path())?)
+    }
+
+    #[inline]
+    pub fn main_page(&self) -> Url {
+        self.base.clone()
+    }
+
+    #[inline]
+    pub fn match_page(&self, id: MatchId) -> Url {
+        self.page(SiteLink::Match(id))
+    }
+
+    #[inline]
+    pub fn team_page(&self, id: TeamId) -> Url {
+        self.page(SiteLink::Team(id))
+    }
+
+    #[inline]
+    pub fn event_page(&self, id: EventId) -> Url {
+        self.page(SiteLink::Event(id))
+    }
+
+    #[inline]
+    pub fn player_page(&self, id: PlayerId) -> Url {
+        self.page(SiteLink::Player(id))
+    }
+
+    /// The page of the results archive with the given number, counting from
+    /// 1.
+    ///
+    /// Like on the site, the first page has no `page` parameter.
+    pub fn results_page(&self, page: u32) -> Url {
+        // A plain path always joins
+        let mut url = self.join(RESULTS_PATH).unwrap();
+
+        if page > 1 {
+            url.query_pairs_mut().append_pair("page", &page.to_string());
+        }
+
+        url
+    }
+
+    /// The number of the page of the results archive the given link (which
+    /// may be relative) points at, if it points at one.
+    pub fn results_page_number(&self, href: &str) -> Option<u32> {
+        let url = self.resolve(href)?;
+
+        if url.origin() != self.base.origin() || url.path().strip_prefix(self.base.path())? != RESULTS_PATH {
+            return None;
+        }
+
+        match url.query_pairs().find(|(name, _)| name == "page") {
+            Some((_, page)) => page.parse().ok().filter(|&page| page > 0),
+            None => Some(1)
+        }
+    }
+
+    /// The URL of the page the given link points at.
+    ///
+    /// The site adds a slug after the ID (e.g. `/12345/team-a-vs-team-b`),
+    /// but doesn't need it.
+    pub fn page(&self, link: SiteLink) -> Url {
+        let path = match link {
+            SiteLink::Match(id) => id.to_string(),
+            other => other.key()
+        };
+
+        // Paths made of keywords and numbers always join
+        self.join(&path).unwrap()
+    }
+
+    fn join(&self, path: &str) -> Option<Url> {
+        // Site-absolute paths are relative to the base URL's path, which is
+        // only the root for the live site
+        let path = match path.strip_prefix('/') {
+            Some(rest) if !rest.starts_with('/') => rest,
+            _ => path
+        };
+
+        self.base.join(path).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn page_urls() {
+        let site = Site::default();
+        assert_eq!(site.main_page().as_str(), "https://www.over.gg/");
+        assert_eq!(site.match_page(MatchId(12345)).as_str(), "https://www.over.gg/12345");
+        assert_eq!(site.team_page(TeamId(7)).as_str(), "https://www.over.gg/team/7");
+
+        let mirror = Site::new("http://127.0.0.1:8080/overgg").unwrap();
+        assert_eq!(mirror.main_page().as_str(), "http://127.0.0.1:8080/overgg/");
+        assert_eq!(mirror.player_page(PlayerId(3)).as_str(), "http://127.0.0.1:8080/overgg/player/3");
+
+        assert_eq!(site.results_page(1).as_str(), "https://www.over.gg/matches/results");
+        assert_eq!(mirror.results_page(2).as_str(), "http://127.0.0.1:8080/overgg/matches/results?page=2");
+    }
+
+    #[test]
+    fn results_page_numbers() {
+        let site = Site::default();
+        assert_eq!(site.results_page_number("/matches/results"), Some(1));
+        assert_eq!(site.results_page_number("https://www.over.gg/matches/results?page=12"), Some(12));
+        assert_eq!(site.results_page_number(site.results_page(3).as_str()), Some(3));
+        assert_eq!(site.results_page_number("/matches/results?page=0"), None);
+        assert_eq!(site.results_page_number("/matches/results?page=last"), None);
+        assert_eq!(site.results_page_number("/matches"), None);
+        assert_eq!(site.results_page_number("https://example.com/matches/results"), None);
+
+        let mirror = Site::new("http://127.0.0.1:8080/overgg").unwrap();
+        assert_eq!(mirror.results_page_number("/matches/results?page=2"), Some(2));
+        assert_eq!(mirror.results_page_number("http://127.0.0.1:8080/matches/results"), None);
+    }
+
+    #[test]
+    fn invalid_base_urls() {
+        assert!(Site::new("mailto:someone@example.com").is_err());
+        assert!(Site::new("data:text/plain,over.gg").is_err());
+        assert!(Site::new("not a url").is_err());
+    }
+
+    #[test]
+    fn resolves_links() {
+        let mirror = Site::new("http://127.0.0.1:8080/overgg/").unwrap();
+
+        assert_eq!(
+            mirror.resolve("/stream/1").unwrap().as_str(),
+            "http://127.0.0.1:8080/overgg/stream/1"
+        );
+        assert_eq!(
+            mirror.resolve("https://www.twitch.tv/a").unwrap().as_str(),
+            "https://www.twitch.tv/a"
+        );
+
+        assert_eq!(mirror.link("/123/a-vs-b"), Some(SiteLink::Match(MatchId(123))));
+        assert_eq!(
+            mirror.link("http://127.0.0.1:8080/overgg/event/4/cup"),
+            Some(SiteLink::Event(EventId(4)))
+        );
+        // Links to the live site aren't links to the mirror
+        assert_eq!(mirror.link("https://www.over.gg/123/a-vs-b"), None);
+        assert_eq!(mirror.link("http://127.0.0.1:8080/123/a-vs-b"), None);
+    }
+
+    #[test]
+    fn unwraps_redirects() {
+        let site = Site::default();
+
+        assert_eq!(
+            site.resolve_target("/go?url=https%3A%2F%2Fwww.twitch.tv%2Fa").unwrap().as_str(),
+            "https://www.twitch.tv/a"
+        );
+        // Not a redirect, just a page with a query string
+        assert_eq!(
+            site.resolve_target("/matches/results?page=2").unwrap().as_str(),
+            "https://www.over.gg/matches/results?page=2"
+        );
+        assert_eq!(
+            site.resolve_target("/search?to=https://www.twitch.tv/a").unwrap().as_str(),
+            "https://www.over.gg/search?to=https://www.twitch.tv/a"
+        );
+        // Redirect pages are under a mirror's path too
+        assert_eq!(
+            Site::new("http://127.0.0.1:8080/overgg").unwrap()
+                .resolve_target("/go?url=https://www.twitch.tv/a").unwrap().as_str(),
+            "https://www.twitch.tv/a"
+        );
+        // Only links on the site itself are redirects
+        assert_eq!(
+            site.resolve_target("https://example.com/go?url=https://www.twitch.tv/a").unwrap().as_str(),
+            "https://example.com/go?url=https://www.twitch.tv/a"
+        );
+    }
+}
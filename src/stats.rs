@@ -0,0 +1,540 @@
+//! Team form and statistics over collections of completed matches.
+//!
+//! Everything here works on the crate's data structs alone, so it gives the
+//! same results for matches that were just scraped as for ones loaded from
+//! `storage` or replayed from an `archive`. As with `head_to_head`, teams are
+//! told apart by name (ignoring case and surrounding whitespace).
+//!
+//! ```rust
+//! # use overgg_scraper::data_structs::MatchBriefInfo;
+//! # use overgg_scraper::stats::Stats;
+//! # let matches: Vec<MatchBriefInfo> = vec![];
+//! let stats = Stats::new(&matches);
+//!
+//! for team in stats.teams() {
+//!     println!("{}: {:?}", team.team, team.series);
+//! }
+//! ```
+
+use crate::data_structs::{EventInfo, LiveMatchState, MatchBriefInfo, Team, TeamCompletedMatchBriefInfo};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+
+/// Wins, losses and draws from one team's point of view.
+#[derive(Default, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub struct Record {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32
+}
+
+impl Record {
+    #[inline]
+    pub fn played(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    /// The fraction of games that were won, if any were played.
+    ///
+    /// Draws count as games played that weren't won.
+    #[inline]
+    pub fn win_rate(&self) -> Option<f64> {
+        match self.played() {
+            0 => None,
+            played => Some(f64::from(self.wins) / f64::from(played))
+        }
+    }
+
+    /// Adds a game with the given scores for the team and its opponent.
+    pub(crate) fn add(&mut self, score: u32, opponent_score: u32) {
+        if score > opponent_score {
+            self.wins += 1;
+        } else if score < opponent_score {
+            self.losses += 1;
+        } else {
+            self.draws += 1;
+        }
+    }
+}
+
+/// What team names are compared by, which ignores case and surrounding
+/// whitespace.
+pub(crate) fn team_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// The names of both teams in a match, if both are known.
+#[inline]
+pub(crate) fn known_teams(teams: &[TeamCompletedMatchBriefInfo; 2]) -> Option<[&str; 2]> {
+    Some([teams[0].slot.known_name()?, teams[1].slot.known_name()?])
+}
+
+/// A run of consecutive results of the same kind.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub enum Streak {
+    Wins(u32),
+    Losses(u32),
+    Draws(u32)
+}
+
+impl Streak {
+    /// How many results the streak is made of.
+    #[inline]
+    pub fn games(&self) -> u32 {
+        match *self {
+            Streak::Wins(len) | Streak::Losses(len) | Streak::Draws(len) => len
+        }
+    }
+
+    /// Extends the streak with a result, or starts a new one if the result is
+    /// different.
+    fn extend(streak: Option<Streak>, result: Streak) -> Streak {
+        match (streak, result) {
+            (Some(Streak::Wins(len)), Streak::Wins(_)) => Streak::Wins(len + 1),
+            (Some(Streak::Losses(len)), Streak::Losses(_)) => Streak::Losses(len + 1),
+            (Some(Streak::Draws(len)), Streak::Draws(_)) => Streak::Draws(len + 1),
+            (_, result) => result
+        }
+    }
+}
+
+/// The kinds of map, which each have their own rules.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub enum MapMode {
+    Control,
+    Escort,
+    Hybrid,
+    Assault,
+    Push,
+    Flashpoint,
+    Clash
+}
+
+/// The mode of every map that has been played competitively, by name in
+/// lowercase.
+const MAP_MODES: &[(&str, MapMode)] = &[
+    ("busan", MapMode::Control),
+    ("ilios", MapMode::Control),
+    ("lijiang tower", MapMode::Control),
+    ("nepal", MapMode::Control),
+    ("oasis", MapMode::Control),
+    ("antarctic peninsula", MapMode::Control),
+    ("samoa", MapMode::Control),
+    ("circuit royal", MapMode::Escort),
+    ("dorado", MapMode::Escort),
+    ("havana", MapMode::Escort),
+    ("junkertown", MapMode::Escort),
+    ("rialto", MapMode::Escort),
+    ("route 66", MapMode::Escort),
+    ("shambali monastery", MapMode::Escort),
+    ("watchpoint: gibraltar", MapMode::Escort),
+    ("blizzard world", MapMode::Hybrid),
+    ("eichenwalde", MapMode::Hybrid),
+    ("hollywood", MapMode::Hybrid),
+    ("king's row", MapMode::Hybrid),
+    ("midtown", MapMode::Hybrid),
+    ("numbani", MapMode::Hybrid),
+    ("paraíso", MapMode::Hybrid),
+    ("hanamura", MapMode::Assault),
+    ("horizon lunar colony", MapMode::Assault),
+    ("paris", MapMode::Assault),
+    ("temple of anubis", MapMode::Assault),
+    ("volskaya industries", MapMode::Assault),
+    ("colosseo", MapMode::Push),
+    ("esperança", MapMode::Push),
+    ("new queen street", MapMode::Push),
+    ("runasapi", MapMode::Push),
+    ("new junk city", MapMode::Flashpoint),
+    ("suravasa", MapMode::Flashpoint),
+    ("hanaoka", MapMode::Clash),
+    ("throne of anubis", MapMode::Clash)
+];
+
+impl MapMode {
+    /// The mode of the map with the given name, if it's a map that's known.
+    pub fn of(map: &str) -> Option<Self> {
+        let map = map.trim().to_lowercase();
+        MAP_MODES.iter().find(|&&(name, _)| name == map).map(|&(_, mode)| mode)
+    }
+}
+
+/// A team's results over a set of matches.
+#[derive(Default, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "derive-serde", derive(Serialize, Deserialize))]
+pub struct TeamStats {
+    /// The team's name as it was first seen.
+    pub team: String,
+    /// Series won, lost and drawn.
+    pub series: Record,
+    /// Maps won and lost, counted from the series scores.
+    ///
+    /// Series scores don't show drawn maps, so there are never any draws.
+    pub maps: Record,
+    /// Records on each map by name, from the match pages that were given.
+    pub by_map: BTreeMap<String, Record>,
+    /// Records in each map mode, from the match pages that were given.
+    ///
+    /// Maps whose mode isn't known (see `MapMode::of`) aren't counted.
+    pub by_mode: BTreeMap<MapMode, Record>,
+    /// Series records in each event, by event name.
+    pub by_event: BTreeMap<String, Record>,
+    /// The team's most recent run of results, if it's played.
+    pub streak: Option<Streak>,
+    pub longest_win_streak: u32,
+    pub longest_loss_streak: u32
+}
+
+impl TeamStats {
+    /// The average of maps won minus maps lost per series, if the team has
+    /// played any.
+    #[inline]
+    pub fn average_map_differential(&self) -> Option<f64> {
+        match self.series.played() {
+            0 => None,
+            played => Some((f64::from(self.maps.wins) - f64::from(self.maps.losses)) / f64::from(played))
+        }
+    }
+
+    fn add_series(&mut self, event: &EventInfo, score: u32, opponent_score: u32) {
+        self.series.add(score, opponent_score);
+        self.maps.wins += score;
+        self.maps.losses += opponent_score;
+        self.by_event.entry(event.name.clone()).or_default().add(score, opponent_score);
+
+        let result = if score > opponent_score {
+            Streak::Wins(1)
+        } else if score < opponent_score {
+            Streak::Losses(1)
+        } else {
+            Streak::Draws(1)
+        };
+        let streak = Streak::extend(self.streak, result);
+
+        match streak {
+            Streak::Wins(len) => self.longest_win_streak = self.longest_win_streak.max(len),
+            Streak::Losses(len) => self.longest_loss_streak = self.longest_loss_streak.max(len),
+            Streak::Draws(_) => {}
+        }
+
+        self.streak = Some(streak);
+    }
+}
+
+/// Works out statistics for the teams in a set of completed matches.
+///
+/// Matches that don't have a score for both teams are left out, but ones that
+/// are still in progress aren't told apart from completed ones, so only pass
+/// in completed matches.
+pub struct Stats<'a> {
+    matches: Vec<&'a MatchBriefInfo>,
+    match_pages: Vec<&'a LiveMatchState>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>
+}
+
+impl<'a> Stats<'a> {
+    pub fn new<I>(matches: I) -> Self
+        where I: IntoIterator<Item = &'a MatchBriefInfo> {
+
+        let mut matches: Vec<&MatchBriefInfo> = matches.into_iter()
+            .filter(|_match| _match.teams.iter().all(|team| team.maps_won.is_some()))
+            .collect();
+
+        // Streaks need the matches in the order they were played. Matches
+        // without a time count as the oldest.
+        matches.sort_by_key(|_match| _match.scheduled_time);
+
+        Self {
+            matches,
+            match_pages: vec![],
+            from: None,
+            to: None
+        }
+    }
+
+    /// Also count the maps completed in the given match pages (see
+    /// `scrapers::match_page`) towards `TeamStats::by_map` and
+    /// `TeamStats::by_mode`.
+    ///
+    /// Match pages are limited by `between` the same way as matches, by
+    /// `LiveMatchState::scheduled_time`.
+    #[inline]
+    pub fn with_match_pages<I>(mut self, match_pages: I) -> Self
+        where I: IntoIterator<Item = &'a LiveMatchState> {
+
+        self.match_pages.extend(match_pages);
+        self
+    }
+
+    /// Only count matches scheduled within `[from, to)`.
+    ///
+    /// Matches without a scheduled time are left out once this is set.
+    #[inline]
+    pub fn between(mut self, from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        self.from = Some(from);
+        self.to = Some(to);
+        self
+    }
+
+    /// Statistics for every team that played, ordered by name.
+    ///
+    /// Matches where either team isn't known are left out.
+    pub fn teams(&self) -> Vec<TeamStats> {
+        let mut teams: HashMap<String, TeamStats> = HashMap::new();
+
+        for _match in self.matches() {
+            let names = match known_teams(&_match.teams) {
+                Some(names) => names,
+                None => continue
+            };
+
+            for &(side, index) in &[(Team::Zero, 0), (Team::One, 1)] {
+                let stats = team_entry(&mut teams, names[index]);
+                let (score, opponent_score) = scores(_match, side);
+                stats.add_series(&_match.event, score, opponent_score);
+            }
+        }
+
+        for state in self.match_pages() {
+            let names = match known_teams(&state.teams) {
+                Some(names) => names,
+                None => continue
+            };
+
+            for map in state.completed_maps() {
+                for &(side, index) in &[(Team::Zero, 0), (Team::One, 1)] {
+                    let stats = team_entry(&mut teams, names[index]);
+                    let (score, opponent_score) = match map.winner() {
+                        Some(winner) if winner == side => (1, 0),
+                        Some(_) => (0, 1),
+                        None => (0, 0)
+                    };
+
+                    stats.by_map.entry(map.name.clone()).or_default().add(score, opponent_score);
+
+                    if let Some(mode) = MapMode::of(&map.name) {
+                        stats.by_mode.entry(mode).or_default().add(score, opponent_score);
+                    }
+                }
+            }
+        }
+
+        let mut teams: Vec<TeamStats> = teams.into_values().collect();
+        teams.sort_by(|a, b| a.team.cmp(&b.team));
+        teams
+    }
+
+    /// Statistics for the team with the given name, if it played.
+    pub fn team(&self, name: &str) -> Option<TeamStats> {
+        let key = team_key(name);
+        self.teams().into_iter().find(|stats| team_key(&stats.team) == key)
+    }
+
+    /**
+    The team's series records grouped by what `tier` says about each event.
+
+    The site doesn't rate events, so how they're grouped is up to you, e.g.:
+
+    ```rust
+    # use overgg_scraper::data_structs::MatchBriefInfo;
+    # use overgg_scraper::stats::Stats;
+    # let matches: Vec<MatchBriefInfo> = vec![];
+    let stats = Stats::new(&matches);
+    let by_tier = stats.by_event_tier("Seoul Dynasty", |event| {
+        if event.name.contains("Overwatch League") { 1 } else { 2 }
+    });
+    ```
+    */
+    pub fn by_event_tier<T, F>(&self, name: &str, tier: F) -> BTreeMap<T, Record>
+        where T: Ord,
+              F: Fn(&EventInfo) -> T {
+
+        let key = team_key(name);
+        let mut records = BTreeMap::new();
+
+        for _match in self.matches() {
+            let names = match known_teams(&_match.teams) {
+                Some(names) => names,
+                None => continue
+            };
+
+            let side = if team_key(names[0]) == key {
+                Team::Zero
+            } else if team_key(names[1]) == key {
+                Team::One
+            } else {
+                continue;
+            };
+
+            let (score, opponent_score) = scores(_match, side);
+            records.entry(tier(&_match.event)).or_insert_with(Record::default).add(score, opponent_score);
+        }
+
+        records
+    }
+
+    /// The matches within the window, oldest first.
+    fn matches(&self) -> impl Iterator<Item = &'a MatchBriefInfo> + '_ {
+        self.matches.iter().cloned().filter(move |_match| self.in_window(_match.scheduled_time))
+    }
+
+    /// The match pages within the window.
+    fn match_pages(&self) -> impl Iterator<Item = &'a LiveMatchState> + '_ {
+        self.match_pages.iter().cloned().filter(move |state| self.in_window(state.scheduled_time))
+    }
+
+    fn in_window(&self, time: Option<DateTime<Utc>>) -> bool {
+        if self.from.is_none() && self.to.is_none() {
+            return true;
+        }
+
+        match time {
+            Some(time) => self.from.is_none_or(|from| time >= from) && self.to.is_none_or(|to| time < to),
+            None => false
+        }
+    }
+}
+
+fn team_entry<'t>(teams: &'t mut HashMap<String, TeamStats>, name: &str) -> &'t mut TeamStats {
+    teams.entry(team_key(name)).or_insert_with(|| TeamStats {
+        team: name.trim().into(),
+        ..TeamStats::default()
+    })
+}
+
+/// The maps won by the team on the given side and by its opponent.
+fn scores(_match: &MatchBriefInfo, side: Team) -> (u32, u32) {
+    let first = u32::from(_match.teams[0].maps_won.unwrap_or(0));
+    let second = u32::from(_match.teams[1].maps_won.unwrap_or(0));
+
+    match side {
+        Team::Zero => (first, second),
+        Team::One => (second, first)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::known_team;
+    use crate::data_structs::{MapInfo, MapStatus, TeamSlot};
+    use chrono::TimeZone;
+
+    fn time(ts: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(ts, 0).unwrap()
+    }
+
+    fn played(event: &str, a: (&str, u8), b: (&str, u8), ts: i64) -> MatchBriefInfo {
+        let mut _match = MatchBriefInfo {
+            teams: [
+                known_team(a.0, Some(a.1)),
+                known_team(b.0, Some(b.1))
+            ],
+            scheduled_time: Some(time(ts)),
+            ..MatchBriefInfo::default()
+        };
+        _match.event.name = event.into();
+        _match
+    }
+
+    fn matches() -> Vec<MatchBriefInfo> {
+        vec![
+            played("League", ("Alpha", 3), ("Bravo", 1), 400),
+            played("League", ("Alpha", 3), ("Charlie", 0), 100),
+            played("Open", ("Bravo", 3), ("Alpha", 2), 500),
+            played("League", ("alpha", 3), ("Charlie", 2), 200),
+            played("Open", ("Charlie", 2), ("Bravo", 2), 300)
+        ]
+    }
+
+    #[test]
+    fn team_stats() {
+        let mut matches = matches();
+        // Doesn't count, as the opponent isn't known
+        let mut undecided = played("League", ("Alpha", 3), ("TBD", 0), 600);
+        undecided.teams[1].slot = TeamSlot::Tbd;
+        matches.push(undecided);
+
+        let stats = Stats::new(&matches);
+
+        let names: Vec<String> = stats.teams().into_iter().map(|team| team.team).collect();
+        assert_eq!(names, vec!["Alpha", "Bravo", "Charlie"]);
+
+        let alpha = stats.team("ALPHA").unwrap();
+        assert_eq!(alpha.series, Record { wins: 3, losses: 1, draws: 0 });
+        assert_eq!(alpha.maps, Record { wins: 11, losses: 6, draws: 0 });
+        assert_eq!(alpha.average_map_differential(), Some(1.25));
+        assert_eq!(alpha.by_event["League"], Record { wins: 3, losses: 0, draws: 0 });
+        assert_eq!(alpha.by_event["Open"], Record { wins: 0, losses: 1, draws: 0 });
+        assert_eq!(alpha.streak, Some(Streak::Losses(1)));
+        assert_eq!(alpha.longest_win_streak, 3);
+        assert_eq!(alpha.longest_loss_streak, 1);
+
+        let charlie = stats.team("Charlie").unwrap();
+        assert_eq!(charlie.streak, Some(Streak::Draws(1)));
+        assert_eq!(charlie.longest_loss_streak, 2);
+
+        assert_eq!(stats.team("Delta"), None);
+    }
+
+    #[test]
+    fn map_modes() {
+        assert_eq!(MapMode::of("King's Row"), Some(MapMode::Hybrid));
+        assert_eq!(MapMode::of(" watchpoint: gibraltar "), Some(MapMode::Escort));
+        assert_eq!(MapMode::of("TBD"), None);
+    }
+
+    #[test]
+    fn date_windows() {
+        let matches = matches();
+        let stats = Stats::new(&matches).between(time(200), time(500));
+
+        let alpha = stats.team("Alpha").unwrap();
+        assert_eq!(alpha.series, Record { wins: 2, losses: 0, draws: 0 });
+        assert_eq!(alpha.streak, Some(Streak::Wins(2)));
+        assert_eq!(alpha.streak.unwrap().games(), 2);
+
+        let by_tier = stats.by_event_tier("Bravo", |event| event.name == "League");
+        assert_eq!(by_tier[&true], Record { wins: 0, losses: 1, draws: 0 });
+        assert_eq!(by_tier[&false], Record { wins: 0, losses: 0, draws: 1 });
+    }
+
+    #[test]
+    fn map_records() {
+        let state = LiveMatchState {
+            teams: [
+                known_team("Alpha", Some(1)),
+                known_team("Bravo", Some(1))
+            ],
+            maps: vec![
+                MapInfo { name: "Ilios".into(), scores: [Some(2), Some(1)], status: MapStatus::Completed },
+                MapInfo { name: "Numbani".into(), scores: [Some(2), Some(3)], status: MapStatus::Completed },
+                MapInfo { name: "Ilios".into(), scores: [Some(1), Some(0)], status: MapStatus::InProgress }
+            ],
+            ..LiveMatchState::default()
+        };
+
+        let stats = Stats::new(&[]).with_match_pages(vec![&state]);
+        let bravo = stats.team("Bravo").unwrap();
+
+        assert_eq!(bravo.by_map["Ilios"], Record { wins: 0, losses: 1, draws: 0 });
+        assert_eq!(bravo.by_map["Numbani"], Record { wins: 1, losses: 0, draws: 0 });
+        assert_eq!(bravo.by_mode[&MapMode::Control], Record { wins: 0, losses: 1, draws: 0 });
+        assert_eq!(bravo.by_mode[&MapMode::Hybrid], Record { wins: 1, losses: 0, draws: 0 });
+        assert_eq!(bravo.series.played(), 0);
+
+        // Pages are only counted within the window
+        let stats = Stats::new(&[]).with_match_pages(vec![&state]).between(time(200), time(500));
+        assert_eq!(stats.team("Bravo"), None);
+
+        let later = LiveMatchState {
+            scheduled_time: Some(time(300)),
+            ..state.clone()
+        };
+        let stats = Stats::new(&[]).with_match_pages(vec![&state, &later]).between(time(200), time(500));
+        assert_eq!(stats.team("Bravo").unwrap().by_map["Numbani"], Record { wins: 1, losses: 0, draws: 0 });
+    }
+}
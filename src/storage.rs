@@ -0,0 +1,848 @@
+//! Persists scraped data in a SQLite database.
+//!
+//! This module can be enabled with the `storage` feature.
+//!
+//! Events and teams get their own tables so that matches can be joined and
+//! queried by them rather than by comparing name strings. Teams are told apart
+//! the same way as everywhere else in the crate, ignoring case and surrounding
+//! whitespace, and only teams that are known get a row: a match whose teams
+//! aren't decided yet stores what's shown for them instead. Matches are keyed by
+//! their ID, so storing the same match again as it goes from upcoming to live
+//! to completed (and its teams go from `TBD` to known) updates the existing
+//! row. Matches scraped without an ID are keyed by a hash of their event and
+//! team names instead (see `MatchBriefInfo::stable_key`), which is only
+//! provisional: the row is taken over by the first match with an ID that
+//! hashes the same.
+//!
+//! The maps of a match are stored separately with `upsert_maps`, from its
+//! match page, and players with `upsert_roster`, from their team's page.
+
+use crate::data_structs::{
+    EventInfo,
+    MatchBriefInfo,
+    MatchBriefType,
+    MatchId,
+    Livestreams,
+    LivestreamInfo,
+    MapInfo,
+    MapStatus,
+    PlayerBriefInfo,
+    PlayerId,
+    TeamPageInfo,
+    TeamSlot,
+    TimeSource
+};
+use crate::error::*;
+use crate::head_to_head::HeadToHead;
+use crate::stats::team_key;
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+use url::Url;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS events (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        series TEXT NOT NULL,
+        UNIQUE (name, series)
+    );
+
+    CREATE TABLE IF NOT EXISTS teams (
+        id INTEGER PRIMARY KEY,
+        -- The name the team was last stored with
+        name TEXT NOT NULL,
+        -- See `stats::team_key`
+        name_key TEXT NOT NULL UNIQUE
+    );
+
+    CREATE TABLE IF NOT EXISTS players (
+        id INTEGER PRIMARY KEY,
+        player_id INTEGER NOT NULL UNIQUE,
+        name TEXT NOT NULL,
+        -- The team whose roster the player was last seen on
+        team_id INTEGER REFERENCES teams (id)
+    );
+
+    CREATE TABLE IF NOT EXISTS matches (
+        id INTEGER PRIMARY KEY,
+        match_id INTEGER UNIQUE,
+        -- `MatchBriefInfo::name_key`, for matches without a `match_id`
+        provisional_key TEXT UNIQUE,
+        event_id INTEGER NOT NULL REFERENCES events (id),
+        -- `NULL` unless the team is known
+        team1_id INTEGER REFERENCES teams (id),
+        team1_maps_won INTEGER,
+        team2_id INTEGER REFERENCES teams (id),
+        team2_maps_won INTEGER,
+        -- See `match_type_to_sql`
+        status TEXT NOT NULL,
+        -- Seconds since the Unix epoch
+        scheduled_time INTEGER,
+        -- See `time_source_to_sql`
+        time_source TEXT,
+        -- See `team_slot_to_sql`
+        team1_slot TEXT,
+        team2_slot TEXT,
+        -- What's shown for a `TeamSlot::Placeholder`
+        team1_placeholder TEXT,
+        team2_placeholder TEXT
+    );
+
+    CREATE INDEX IF NOT EXISTS matches_scheduled_time ON matches (scheduled_time);
+
+    CREATE TABLE IF NOT EXISTS maps (
+        match_id INTEGER NOT NULL,
+        -- Where the map comes in the series, from 0
+        position INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        team1_score INTEGER,
+        team2_score INTEGER,
+        -- See `map_status_to_sql`
+        status TEXT NOT NULL,
+        PRIMARY KEY (match_id, position)
+    );
+
+    CREATE TABLE IF NOT EXISTS streams (
+        name TEXT PRIMARY KEY,
+        title TEXT,
+        viewer_count INTEGER,
+        url TEXT,
+        curated INTEGER NOT NULL,
+        -- Seconds since the Unix epoch
+        last_seen INTEGER NOT NULL
+    );
+";
+
+const SELECT_MATCHES: &str = "
+    SELECT m.match_id, e.name, e.series,
+           COALESCE(t1.name, m.team1_placeholder), m.team1_maps_won,
+           COALESCE(t2.name, m.team2_placeholder), m.team2_maps_won,
+           m.scheduled_time, m.time_source, m.team1_slot, m.team2_slot
+    FROM matches m
+    JOIN events e ON e.id = m.event_id
+    LEFT JOIN teams t1 ON t1.id = m.team1_id
+    LEFT JOIN teams t2 ON t2.id = m.team2_id
+";
+
+/// A SQLite database of scraped data.
+pub struct Store {
+    conn: Connection
+}
+
+impl Store {
+    /// Opens (or creates) the database at the given path.
+    #[inline]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Creates a database that only lives in memory.
+    #[inline]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    /// Uses the given connection, creating any tables that don't exist yet.
+    pub fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+
+        Ok(Self {
+            conn
+        })
+    }
+
+    /// Inserts the given matches of the given type (as passed to
+    /// `MainPageScraper::matches_brief`), updating any that are already
+    /// stored.
+    ///
+    /// Information that a newer scrape is missing (such as a scheduled time)
+    /// doesn't overwrite what's already stored, and neither does a time worked
+    /// out from a countdown overwrite an exact timestamp. Completed matches
+    /// stay completed.
+    pub fn upsert_matches(&mut self, _type: MatchBriefType, matches: &[MatchBriefInfo]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+
+        for _match in matches {
+            let event_id = upsert_event(&tx, &_match.event)?;
+            let team1_id = _match.teams[0].slot.known_name().map(|name| upsert_team(&tx, name)).transpose()?;
+            let team2_id = _match.teams[1].slot.known_name().map(|name| upsert_team(&tx, name)).transpose()?;
+
+            let name_key = _match.name_key();
+            let by_id = match _match.id {
+                Some(id) => tx.query_row(
+                    "SELECT id FROM matches WHERE match_id = ?1",
+                    params![id.0],
+                    |row| row.get::<_, i64>(0)
+                ).optional()?,
+                None => None
+            };
+            // The match may have been stored without its ID before
+            let row_id = match by_id {
+                Some(row_id) => Some(row_id),
+                None => tx.query_row(
+                    "SELECT id FROM matches WHERE match_id IS NULL AND provisional_key = ?1",
+                    params![name_key],
+                    |row| row.get::<_, i64>(0)
+                ).optional()?
+            };
+
+            tx.execute(
+                "INSERT INTO matches (
+                     id, match_id, provisional_key, event_id,
+                     team1_id, team1_maps_won, team2_id, team2_maps_won, status,
+                     scheduled_time, time_source, team1_slot, team2_slot,
+                     team1_placeholder, team2_placeholder
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+                 ON CONFLICT (id) DO UPDATE SET
+                     match_id = excluded.match_id,
+                     provisional_key = excluded.provisional_key,
+                     event_id = excluded.event_id,
+                     team1_id = excluded.team1_id,
+                     team1_maps_won = COALESCE(excluded.team1_maps_won, team1_maps_won),
+                     team2_id = excluded.team2_id,
+                     team2_maps_won = COALESCE(excluded.team2_maps_won, team2_maps_won),
+                     status = CASE
+                         WHEN status = 'completed' THEN status
+                         ELSE excluded.status
+                     END,
+                     scheduled_time = CASE
+                         WHEN excluded.scheduled_time IS NULL THEN scheduled_time
+                         WHEN time_source = 'timestamp' AND excluded.time_source != 'timestamp'
+                             THEN scheduled_time
+                         ELSE excluded.scheduled_time
+                     END,
+                     time_source = CASE
+                         WHEN excluded.scheduled_time IS NULL THEN time_source
+                         WHEN time_source = 'timestamp' AND excluded.time_source != 'timestamp'
+                             THEN time_source
+                         ELSE excluded.time_source
+                     END,
+                     team1_slot = excluded.team1_slot,
+                     team2_slot = excluded.team2_slot,
+                     team1_placeholder = excluded.team1_placeholder,
+                     team2_placeholder = excluded.team2_placeholder",
+                params![
+                    row_id,
+                    _match.id.map(|id| id.0),
+                    // Only matches without an ID are keyed by their names
+                    if _match.id.is_none() { Some(name_key) } else { None },
+                    event_id,
+                    team1_id,
+                    _match.teams[0].maps_won,
+                    team2_id,
+                    _match.teams[1].maps_won,
+                    match_type_to_sql(_type),
+                    _match.scheduled_time.map(|t| t.timestamp()),
+                    _match.time_source.map(time_source_to_sql),
+                    team_slot_to_sql(&_match.teams[0].slot),
+                    team_slot_to_sql(&_match.teams[1].slot),
+                    placeholder_to_sql(&_match.teams[0].slot),
+                    placeholder_to_sql(&_match.teams[1].slot)
+                ]
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Stores the maps of the match with the given ID (see
+    /// `LiveMatchState::maps`), replacing any that are already stored.
+    pub fn upsert_maps(&mut self, id: MatchId, maps: &[MapInfo]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+
+        // The series may have been shortened since the maps were last stored
+        tx.execute("DELETE FROM maps WHERE match_id = ?1", params![id.0])?;
+
+        for (position, map) in maps.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO maps (match_id, position, name, team1_score, team2_score, status)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    id.0,
+                    position as i64,
+                    map.name,
+                    map.scores[0],
+                    map.scores[1],
+                    map_status_to_sql(map.status)
+                ]
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Stores the team on the given team page and the players on its roster,
+    /// updating any that are already stored.
+    ///
+    /// Players who were stored as being on the team but aren't on the roster
+    /// any more are no longer on any team. Players without an ID are left
+    /// out, as there's nothing to tell them apart by.
+    pub fn upsert_roster(&mut self, team: &TeamPageInfo) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        let team_id = upsert_team(&tx, &team.name)?;
+
+        tx.execute("UPDATE players SET team_id = NULL WHERE team_id = ?1", params![team_id])?;
+
+        for player in &team.roster {
+            let id = match player.id {
+                Some(id) => id,
+                None => continue
+            };
+
+            tx.execute(
+                "INSERT INTO players (player_id, name, team_id) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (player_id) DO UPDATE SET
+                     name = excluded.name,
+                     team_id = excluded.team_id",
+                params![id.0, player.name, team_id]
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Inserts the given streams, updating any that are already stored.
+    ///
+    /// `seen_at` is recorded as the last time each of the streams was live.
+    pub fn upsert_livestreams(&mut self, streams: &Livestreams, seen_at: DateTime<Utc>) -> Result<()> {
+        let tx = self.conn.transaction()?;
+
+        let all = streams.curated.iter().map(|s| (s, true))
+            .chain(streams.other_top.iter().map(|s| (s, false)));
+
+        for (stream, curated) in all {
+            tx.execute(
+                "INSERT INTO streams (name, title, viewer_count, url, curated, last_seen)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT (name) DO UPDATE SET
+                     title = excluded.title,
+                     viewer_count = excluded.viewer_count,
+                     url = excluded.url,
+                     curated = excluded.curated,
+                     last_seen = excluded.last_seen",
+                params![
+                    stream.name,
+                    stream.title,
+                    stream.viewer_count,
+                    stream.url.as_ref().map(Url::as_str),
+                    curated,
+                    seen_at.timestamp()
+                ]
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Gets the stored match with the given ID.
+    pub fn match_by_id(&self, id: MatchId) -> Result<Option<MatchBriefInfo>> {
+        Ok(self.conn.query_row(
+            &format!("{} WHERE m.match_id = ?1", SELECT_MATCHES),
+            params![id.0],
+            match_from_row
+        ).optional()?)
+    }
+
+    /// Gets the stored maps of the match with the given ID, in the order
+    /// they're played.
+    pub fn maps_by_match(&self, id: MatchId) -> Result<Vec<MapInfo>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, team1_score, team2_score, status FROM maps
+             WHERE match_id = ?1
+             ORDER BY position"
+        )?;
+        let maps = stmt.query_map(params![id.0], |row| {
+            Ok(MapInfo {
+                name: row.get(0)?,
+                scores: [row.get(1)?, row.get(2)?],
+                status: map_status_from_sql(&row.get::<_, String>(3)?)
+            })
+        })?.collect::<::std::result::Result<_, _>>()?;
+
+        Ok(maps)
+    }
+
+    /// Gets the stored matches that the team with the given name played in,
+    /// ordered by scheduled time.
+    ///
+    /// Names are compared the same way as by `head_to_head`, ignoring case
+    /// and surrounding whitespace.
+    pub fn matches_by_team(&self, name: &str) -> Result<Vec<MatchBriefInfo>> {
+        self.query_matches("WHERE t1.name_key = ?1 OR t2.name_key = ?1", params![team_key(name)])
+    }
+
+    /// Gets the stored players who were last seen on the roster of the team
+    /// with the given name, in the order they were first stored.
+    pub fn players_by_team(&self, name: &str) -> Result<Vec<PlayerBriefInfo>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT p.player_id, p.name FROM players p
+             JOIN teams t ON t.id = p.team_id
+             WHERE t.name_key = ?1
+             ORDER BY p.id"
+        )?;
+        let players = stmt.query_map(params![team_key(name)], |row| {
+            Ok(PlayerBriefInfo {
+                id: Some(PlayerId(row.get(0)?)),
+                name: row.get(1)?
+            })
+        })?.collect::<::std::result::Result<_, _>>()?;
+
+        Ok(players)
+    }
+
+    /// Gets the history between the two teams with the given names from the
+    /// stored completed matches.
+    ///
+    /// Names are compared the same way as by `HeadToHead::from_matches`,
+    /// ignoring case and surrounding whitespace.
+    pub fn head_to_head(&self, team: &str, opponent: &str) -> Result<HeadToHead> {
+        let completed = self.query_matches(
+            "WHERE m.status = ?1",
+            params![match_type_to_sql(MatchBriefType::Completed)]
+        )?;
+
+        Ok(HeadToHead::from_matches(team, opponent, &completed))
+    }
+
+    /// Gets the stored matches that were part of the event with the given name,
+    /// ordered by scheduled time.
+    pub fn matches_by_event(&self, name: &str) -> Result<Vec<MatchBriefInfo>> {
+        self.query_matches("WHERE e.name = ?1", params![name])
+    }
+
+    /// Gets the stored matches scheduled within `[from, to)`, ordered by
+    /// scheduled time.
+    pub fn matches_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<MatchBriefInfo>> {
+        self.query_matches(
+            "WHERE m.scheduled_time >= ?1 AND m.scheduled_time < ?2",
+            params![from.timestamp(), to.timestamp()]
+        )
+    }
+
+    /// Gets the streams that were last seen live at or after the given time.
+    pub fn livestreams_seen_since(&self, since: DateTime<Utc>) -> Result<Livestreams> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, title, viewer_count, url, curated FROM streams
+             WHERE last_seen >= ?1
+             ORDER BY viewer_count DESC"
+        )?;
+        let rows = stmt.query_map(params![since.timestamp()], |row| {
+            let info = LivestreamInfo {
+                name: row.get(0)?,
+                title: row.get(1)?,
+                viewer_count: row.get(2)?,
+                url: row.get::<_, Option<String>>(3)?.and_then(|url| Url::parse(&url).ok())
+            };
+
+            Ok((info, row.get::<_, bool>(4)?))
+        })?;
+
+        let mut streams = Livestreams::default();
+
+        for row in rows {
+            match row? {
+                (info, true) => streams.curated.push(info),
+                (info, false) => streams.other_top.push(info)
+            }
+        }
+
+        Ok(streams)
+    }
+
+    fn query_matches<P: rusqlite::Params>(&self, filter: &str, params: P) -> Result<Vec<MatchBriefInfo>> {
+        let mut stmt = self.conn.prepare(
+            &format!("{} {} ORDER BY m.scheduled_time, m.id", SELECT_MATCHES, filter)
+        )?;
+        let matches = stmt.query_map(params, match_from_row)?
+            .collect::<::std::result::Result<_, _>>()?;
+
+        Ok(matches)
+    }
+}
+
+fn upsert_event(conn: &Connection, event: &EventInfo) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO events (name, series) VALUES (?1, ?2) ON CONFLICT DO NOTHING",
+        params![event.name, event.series]
+    )?;
+
+    Ok(conn.query_row(
+        "SELECT id FROM events WHERE name = ?1 AND series = ?2",
+        params![event.name, event.series],
+        |row| row.get(0)
+    )?)
+}
+
+/// Only for known teams, which are told apart by `stats::team_key`.
+fn upsert_team(conn: &Connection, name: &str) -> Result<i64> {
+    let key = team_key(name);
+
+    conn.execute(
+        "INSERT INTO teams (name, name_key) VALUES (?1, ?2)
+         ON CONFLICT (name_key) DO UPDATE SET name = excluded.name",
+        params![name.trim(), key]
+    )?;
+
+    Ok(conn.query_row("SELECT id FROM teams WHERE name_key = ?1", params![key], |row| row.get(0))?)
+}
+
+fn match_type_to_sql(_type: MatchBriefType) -> &'static str {
+    match _type {
+        MatchBriefType::InFuture => "upcoming",
+        MatchBriefType::Live => "live",
+        MatchBriefType::Completed => "completed"
+    }
+}
+
+fn time_source_to_sql(source: TimeSource) -> &'static str {
+    match source {
+        TimeSource::Timestamp => "timestamp",
+        TimeSource::Countdown => "countdown",
+        TimeSource::Live => "live"
+    }
+}
+
+fn time_source_from_sql(source: &str) -> Option<TimeSource> {
+    match source {
+        "timestamp" => Some(TimeSource::Timestamp),
+        "countdown" => Some(TimeSource::Countdown),
+        "live" => Some(TimeSource::Live),
+        _ => None
+    }
+}
+
+fn map_status_to_sql(status: MapStatus) -> &'static str {
+    match status {
+        MapStatus::Upcoming => "upcoming",
+        MapStatus::InProgress => "in_progress",
+        MapStatus::Completed => "completed"
+    }
+}
+
+fn map_status_from_sql(status: &str) -> MapStatus {
+    match status {
+        "in_progress" => MapStatus::InProgress,
+        "completed" => MapStatus::Completed,
+        _ => MapStatus::Upcoming
+    }
+}
+
+/// Known teams are stored as `NULL`, as the team is stored already.
+/// Placeholders are stored along with their text (see `placeholder_to_sql`).
+fn team_slot_to_sql(slot: &TeamSlot) -> Option<String> {
+    match *slot {
+        TeamSlot::Known(_) => None,
+        TeamSlot::Tbd => Some("tbd".into()),
+        TeamSlot::Placeholder(_) => Some("placeholder".into()),
+        TeamSlot::WinnerOf(id) => Some(format!("winner:{}", id)),
+        TeamSlot::LoserOf(id) => Some(format!("loser:{}", id))
+    }
+}
+
+fn placeholder_to_sql(slot: &TeamSlot) -> Option<&str> {
+    match *slot {
+        TeamSlot::Placeholder(ref text) => Some(text),
+        _ => None
+    }
+}
+
+/// The reverse of `team_slot_to_sql`, given the team's stored name or the
+/// placeholder's text.
+fn team_slot_from_sql(slot: Option<&str>, name: Option<String>) -> TeamSlot {
+    let name = name.unwrap_or_default();
+    let slot = match slot {
+        Some(slot) => slot,
+        None => return TeamSlot::Known(name)
+    };
+    let id = |id: &str| id.parse().ok().map(MatchId);
+
+    match slot.split_once(':') {
+        Some(("winner", match_id)) => id(match_id).map_or(TeamSlot::Tbd, TeamSlot::WinnerOf),
+        Some(("loser", match_id)) => id(match_id).map_or(TeamSlot::Tbd, TeamSlot::LoserOf),
+        _ if slot == "placeholder" => TeamSlot::Placeholder(name),
+        _ => TeamSlot::Tbd
+    }
+}
+
+fn match_from_row(row: &Row) -> rusqlite::Result<MatchBriefInfo> {
+    let mut _match = MatchBriefInfo {
+        id: row.get::<_, Option<u32>>(0)?.map(MatchId),
+        scheduled_time: row.get::<_, Option<i64>>(7)?
+                           .and_then(|ts| Utc.timestamp_opt(ts, 0).single()),
+        time_source: row.get::<_, Option<String>>(8)?.as_deref().and_then(time_source_from_sql),
+        ..MatchBriefInfo::default()
+    };
+    _match.event.name = row.get(1)?;
+    _match.event.series = row.get(2)?;
+    _match.teams[0].slot = team_slot_from_sql(row.get::<_, Option<String>>(9)?.as_deref(), row.get(3)?);
+    _match.teams[0].maps_won = row.get(4)?;
+    _match.teams[1].slot = team_slot_from_sql(row.get::<_, Option<String>>(10)?.as_deref(), row.get(5)?);
+    _match.teams[1].maps_won = row.get(6)?;
+
+    Ok(_match)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::known_team;
+
+    fn time(ts: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(ts, 0).unwrap()
+    }
+
+    fn upcoming(id: u32, teams: [&str; 2], ts: i64) -> MatchBriefInfo {
+        let mut _match = MatchBriefInfo {
+            id: Some(MatchId(id)),
+            scheduled_time: Some(time(ts)),
+            time_source: Some(TimeSource::Timestamp),
+            ..MatchBriefInfo::default()
+        };
+        _match.event.name = "Overwatch League".into();
+        _match.event.series = "Stage 1".into();
+        _match.teams = [
+            known_team(teams[0], None),
+            known_team(teams[1], None)
+        ];
+        _match
+    }
+
+    #[test]
+    fn matches_are_updated_in_place() {
+        let mut store = Store::open_in_memory().unwrap();
+        let mut _match = upcoming(1, ["Seoul Dynasty", "London Spitfire"], 100);
+        store.upsert_matches(MatchBriefType::InFuture, &[_match.clone()]).unwrap();
+
+        // The completed card doesn't show a time, which shouldn't erase it
+        _match.teams[0].maps_won = Some(3);
+        _match.teams[1].maps_won = Some(1);
+        _match.scheduled_time = None;
+        _match.time_source = None;
+        store.upsert_matches(MatchBriefType::Completed, &[_match.clone()]).unwrap();
+
+        let stored = store.match_by_id(MatchId(1)).unwrap().unwrap();
+        assert_eq!(stored.teams[0].maps_won, Some(3));
+        assert_eq!(stored.scheduled_time, Some(time(100)));
+        assert_eq!(stored.time_source, Some(TimeSource::Timestamp));
+
+        // Nor should a time worked out from a countdown
+        _match.scheduled_time = Some(time(160));
+        _match.time_source = Some(TimeSource::Countdown);
+        store.upsert_matches(MatchBriefType::InFuture, &[_match.clone()]).unwrap();
+
+        let stored = store.match_by_id(MatchId(1)).unwrap().unwrap();
+        assert_eq!(stored.scheduled_time, Some(time(100)));
+        assert_eq!(stored.time_source, Some(TimeSource::Timestamp));
+        assert_eq!(store.matches_by_team("London Spitfire").unwrap().len(), 1);
+        assert_eq!(store.match_by_id(MatchId(2)).unwrap(), None);
+    }
+
+    #[test]
+    fn team_slots() {
+        let mut store = Store::open_in_memory().unwrap();
+        let mut _match = upcoming(1, ["TBD", "TBD"], 100);
+        _match.teams[0].slot = TeamSlot::WinnerOf(MatchId(7));
+        _match.teams[1].slot = TeamSlot::LoserOf(MatchId(8));
+        store.upsert_matches(MatchBriefType::InFuture, &[_match.clone()]).unwrap();
+
+        assert_eq!(store.match_by_id(MatchId(1)).unwrap().unwrap(), _match);
+
+        // Once the teams are known, the slots follow
+        _match.teams[0].slot = TeamSlot::Known("Seoul Dynasty".into());
+        store.upsert_matches(MatchBriefType::InFuture, &[_match.clone()]).unwrap();
+
+        let stored = store.match_by_id(MatchId(1)).unwrap().unwrap();
+        assert_eq!(stored.teams[0].slot, TeamSlot::Known("Seoul Dynasty".into()));
+        assert_eq!(stored.teams[1].slot, TeamSlot::LoserOf(MatchId(8)));
+
+        _match.teams[1].slot = TeamSlot::Placeholder("Winner of Semifinal 2".into());
+        store.upsert_matches(MatchBriefType::InFuture, &[_match.clone()]).unwrap();
+        assert_eq!(store.match_by_id(MatchId(1)).unwrap().unwrap(), _match);
+
+        // Only known teams are teams
+        let mut undecided = upcoming(2, ["TBD", "TBD"], 200);
+        undecided.teams[0].slot = TeamSlot::Tbd;
+        undecided.teams[1].slot = TeamSlot::Placeholder("Seoul Dynasty".into());
+        store.upsert_matches(MatchBriefType::InFuture, &[undecided.clone()]).unwrap();
+
+        assert_eq!(store.match_by_id(MatchId(2)).unwrap().unwrap(), undecided);
+        assert_eq!(store.matches_by_team("TBD").unwrap(), vec![]);
+        assert_eq!(store.matches_by_team("Winner of Semifinal 2").unwrap(), vec![]);
+        assert_eq!(store.matches_by_team("Seoul Dynasty").unwrap(), vec![_match]);
+    }
+
+    #[test]
+    fn provisional_keys() {
+        let mut store = Store::open_in_memory().unwrap();
+        let mut _match = upcoming(1, ["Seoul Dynasty", "London Spitfire"], 100);
+        _match.id = None;
+        store.upsert_matches(MatchBriefType::InFuture, &[_match.clone(), _match.clone()]).unwrap();
+        assert_eq!(store.matches_by_event("Overwatch League").unwrap(), vec![_match.clone()]);
+
+        // Once the match has an ID, that's what it's keyed by
+        _match.id = Some(MatchId(1));
+        store.upsert_matches(MatchBriefType::InFuture, &[_match.clone()]).unwrap();
+        assert_eq!(store.matches_by_event("Overwatch League").unwrap(), vec![_match.clone()]);
+
+        // Another match between the same teams doesn't take over the row
+        let mut rematch = _match.clone();
+        rematch.id = None;
+        store.upsert_matches(MatchBriefType::InFuture, &[rematch]).unwrap();
+        assert_eq!(store.matches_by_event("Overwatch League").unwrap().len(), 2);
+        assert_eq!(store.match_by_id(MatchId(1)).unwrap(), Some(_match));
+    }
+
+    #[test]
+    fn maps() {
+        let mut store = Store::open_in_memory().unwrap();
+        let map = |name: &str, scores: [Option<u8>; 2], status| MapInfo {
+            name: name.into(),
+            scores,
+            status
+        };
+
+        store.upsert_maps(MatchId(1), &[
+            map("Ilios", [Some(2), Some(0)], MapStatus::Completed),
+            map("King's Row", [Some(1), Some(0)], MapStatus::InProgress),
+            map("TBD", [None, None], MapStatus::Upcoming)
+        ]).unwrap();
+
+        let maps = vec![
+            map("Ilios", [Some(2), Some(0)], MapStatus::Completed),
+            map("King's Row", [Some(3), Some(2)], MapStatus::Completed)
+        ];
+        store.upsert_maps(MatchId(1), &maps).unwrap();
+
+        assert_eq!(store.maps_by_match(MatchId(1)).unwrap(), maps);
+        assert_eq!(store.maps_by_match(MatchId(2)).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn match_queries() {
+        let mut store = Store::open_in_memory().unwrap();
+        let mut other_event = upcoming(3, ["Seoul Dynasty", "Dallas Fuel"], 300);
+        other_event.event.name = "Overwatch World Cup".into();
+
+        store.upsert_matches(MatchBriefType::InFuture, &[
+            upcoming(2, ["Seoul Dynasty", "Dallas Fuel"], 200),
+            upcoming(1, ["Seoul Dynasty", "London Spitfire"], 100),
+            other_event
+        ]).unwrap();
+
+        let ids = |matches: Vec<MatchBriefInfo>| matches.iter().map(|m| m.id.unwrap().0).collect::<Vec<_>>();
+
+        assert_eq!(ids(store.matches_by_team("Seoul Dynasty").unwrap()), vec![1, 2, 3]);
+        assert_eq!(ids(store.matches_by_team(" dallas FUEL").unwrap()), vec![2, 3]);
+        assert_eq!(ids(store.matches_by_event("Overwatch League").unwrap()), vec![1, 2]);
+        assert_eq!(ids(store.matches_between(time(100), time(300)).unwrap()), vec![1, 2]);
+    }
+
+    #[test]
+    fn rosters() {
+        let mut store = Store::open_in_memory().unwrap();
+        let player = |id: u32, name: &str| PlayerBriefInfo {
+            id: Some(PlayerId(id)),
+            name: name.into()
+        };
+        let mut seoul = TeamPageInfo {
+            name: "Seoul Dynasty".into(),
+            roster: vec![player(1, "Fleta"), player(2, "Munchkin"), PlayerBriefInfo::default()],
+            ..TeamPageInfo::default()
+        };
+        store.upsert_roster(&seoul).unwrap();
+        assert_eq!(store.players_by_team("seoul dynasty").unwrap(), vec![player(1, "Fleta"), player(2, "Munchkin")]);
+
+        // Players move between teams
+        seoul.roster = vec![player(1, "Fleta")];
+        store.upsert_roster(&seoul).unwrap();
+        store.upsert_roster(&TeamPageInfo {
+            name: "Shanghai Dragons".into(),
+            roster: vec![player(3, "Diem"), player(1, "FLETA")],
+            ..TeamPageInfo::default()
+        }).unwrap();
+
+        assert_eq!(store.players_by_team("Seoul Dynasty").unwrap(), vec![]);
+        assert_eq!(store.players_by_team("Shanghai Dragons").unwrap(), vec![player(1, "FLETA"), player(3, "Diem")]);
+
+        // The team is the same one that plays matches
+        store.upsert_matches(MatchBriefType::InFuture, &[upcoming(1, ["shanghai dragons", "Seoul Dynasty"], 100)]).unwrap();
+        assert_eq!(store.players_by_team("Shanghai Dragons").unwrap().len(), 2);
+        assert_eq!(store.matches_by_team("Shanghai Dragons").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn head_to_head() {
+        let mut store = Store::open_in_memory().unwrap();
+        let mut first = upcoming(1, ["Seoul Dynasty", "London Spitfire"], 100);
+        first.teams[0].maps_won = Some(3);
+        first.teams[1].maps_won = Some(1);
+        let mut second = upcoming(2, ["London Spitfire", "Seoul Dynasty"], 200);
+        second.teams[0].maps_won = Some(3);
+        second.teams[1].maps_won = Some(2);
+
+        store.upsert_matches(MatchBriefType::Completed, &[first, second]).unwrap();
+        store.upsert_matches(MatchBriefType::InFuture, &[upcoming(3, ["Seoul Dynasty", "Dallas Fuel"], 300)]).unwrap();
+
+        // Live matches have scores too, but aren't over
+        let mut live = upcoming(4, ["london spitfire ", "Seoul Dynasty"], 400);
+        live.teams[0].maps_won = Some(1);
+        live.teams[1].maps_won = Some(0);
+        store.upsert_matches(MatchBriefType::Live, &[live.clone()]).unwrap();
+
+        let h2h = store.head_to_head("seoul dynasty", " London Spitfire").unwrap();
+        assert_eq!(h2h.series.wins, 1);
+        assert_eq!(h2h.series.losses, 1);
+        assert_eq!(h2h.maps.wins, 5);
+        assert_eq!(h2h.meetings[0].id, Some(MatchId(2)));
+
+        // Once it's over it counts, and a stale scrape doesn't take that back
+        live.teams[0].maps_won = Some(3);
+        store.upsert_matches(MatchBriefType::Completed, &[live.clone()]).unwrap();
+        store.upsert_matches(MatchBriefType::Live, &[live]).unwrap();
+
+        let h2h = store.head_to_head("Seoul Dynasty", "London Spitfire").unwrap();
+        assert_eq!(h2h.series.losses, 2);
+        assert_eq!(h2h.meetings[0].id, Some(MatchId(4)));
+    }
+
+    #[test]
+    fn livestreams() {
+        let mut store = Store::open_in_memory().unwrap();
+        let stream = |name: &str, viewer_count| LivestreamInfo {
+            name: name.into(),
+            title: None,
+            viewer_count: Some(viewer_count),
+            url: Url::parse(&format!("https://www.twitch.tv/{}", name)).ok()
+        };
+
+        store.upsert_livestreams(&Livestreams {
+            curated: vec![stream("a", 10)],
+            other_top: vec![stream("b", 20)]
+        }, time(100)).unwrap();
+        store.upsert_livestreams(&Livestreams {
+            curated: vec![stream("a", 30)],
+            other_top: vec![]
+        }, time(200)).unwrap();
+
+        assert_eq!(store.livestreams_seen_since(time(200)).unwrap(), Livestreams {
+            curated: vec![stream("a", 30)],
+            other_top: vec![]
+        });
+        assert_eq!(store.livestreams_seen_since(time(0)).unwrap().other_top, vec![stream("b", 20)]);
+
+        let unlinked = LivestreamInfo {
+            url: None,
+            ..stream("c", 40)
+        };
+        store.upsert_livestreams(&Livestreams {
+            curated: vec![unlinked.clone()],
+            other_top: vec![]
+        }, time(300)).unwrap();
+        assert_eq!(store.livestreams_seen_since(time(300)).unwrap().curated, vec![unlinked]);
+    }
+}
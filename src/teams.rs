@@ -0,0 +1,428 @@
+//! Resolves the different names a team goes by to one identity.
+//!
+//! The site refers to the same team by its full name in some places and by an
+//! abbreviation in others, teams rebrand, and matches whose teams aren't
+//! decided yet list "TBD". A `TeamResolver` maps all of these to a
+//! `CanonicalTeam` using an alias table written in TOML:
+//!
+//! ```toml
+//! # Names that stand in for a team that isn't known yet, on top of the
+//! # built-in ones ("TBD", "TBA" and the like)
+//! placeholders = ["Qualifier"]
+//!
+//! [[team]]
+//! name = "Gen.G Esports"
+//! # The number in the team's over.gg URL, if you know it
+//! id = 12
+//! aliases = ["GEN", "Gen.G"]
+//!
+//! # What the team was called before, oldest first, with the date each name
+//! # stopped being used
+//! [[team.former]]
+//! name = "Seoul Dynasty"
+//! aliases = ["SEO"]
+//! until = 2023-10-01
+//! ```
+//!
+//! `TeamResolver::canonicalize` rewrites the team names in scraped matches to
+//! the canonical ones, so that `head_to_head`, `stats` and `rating` see one
+//! team rather than several.
+
+use crate::data_structs::{MatchBriefInfo, TeamId, TeamSlot, PLACEHOLDER_NAMES};
+use crate::error::*;
+use crate::stats::team_key;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::fs;
+use std::path::Path;
+
+/// The alias table as written in TOML.
+#[derive(Debug, Default, serde_derive::Deserialize)]
+struct AliasTable {
+    #[serde(default)]
+    placeholders: Vec<String>,
+    #[serde(default)]
+    team: Vec<AliasEntry>
+}
+
+#[derive(Debug, serde_derive::Deserialize)]
+struct AliasEntry {
+    name: String,
+    id: Option<u32>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    former: Vec<FormerName>
+}
+
+#[derive(Debug, serde_derive::Deserialize)]
+struct FormerName {
+    name: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    until: ::toml::value::Datetime
+}
+
+/// One of the names a team has gone by.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TeamName {
+    pub name: String,
+    /// Other ways of writing the name, such as its abbreviation.
+    pub aliases: Vec<String>,
+    /// When the team stopped going by this name, if it has.
+    pub until: Option<DateTime<Utc>>
+}
+
+impl TeamName {
+    fn matches(&self, key: &str) -> bool {
+        team_key(&self.name) == key || self.aliases.iter().any(|alias| team_key(alias) == key)
+    }
+}
+
+/// A team, however it's referred to.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CanonicalTeam {
+    pub id: Option<TeamId>,
+    /// Every name the team has gone by, oldest first. The last one is the
+    /// team's current name.
+    pub names: Vec<TeamName>
+}
+
+impl CanonicalTeam {
+    /// The team's current name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        // Every team is created with its current name
+        &self.names.last().unwrap().name
+    }
+
+    /// The name the team went by at the given time.
+    pub fn name_at(&self, time: DateTime<Utc>) -> &str {
+        self.names.iter()
+            .find(|name| name.until.is_none_or(|until| time < until))
+            .map_or(self.name(), |name| &name.name)
+    }
+
+    /// Which of the team's names the given name is, if any.
+    fn matching_name(&self, key: &str) -> Option<usize> {
+        self.names.iter().position(|name| name.matches(key))
+    }
+
+    /// Whether the name at the given index was in use at the given time.
+    fn in_use(&self, index: usize, time: DateTime<Utc>) -> bool {
+        let from = index.checked_sub(1).and_then(|previous| self.names[previous].until);
+
+        from.is_none_or(|from| time >= from) && self.names[index].until.is_none_or(|until| time < until)
+    }
+}
+
+/// What a team name refers to.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Resolved<'a> {
+    Team(&'a CanonicalTeam),
+    /// The name stands in for a team that isn't known yet.
+    Placeholder,
+    /// The name isn't in the alias table.
+    Unknown
+}
+
+/// Maps team names and IDs to `CanonicalTeam`s using an alias table.
+#[derive(Debug, Default, Clone)]
+pub struct TeamResolver {
+    teams: Vec<CanonicalTeam>,
+    placeholders: Vec<String>
+}
+
+impl TeamResolver {
+    /// A resolver that only knows the built-in placeholder names.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A resolver using the given alias table (see the module docs for the
+    /// format).
+    pub fn from_toml(table: &str) -> Result<Self> {
+        let mut resolver = Self::new();
+        resolver.add_toml(table)?;
+
+        Ok(resolver)
+    }
+
+    /// A resolver using the alias table in the given file.
+    #[inline]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Adds the teams and placeholders in another alias table.
+    ///
+    /// Where tables disagree about a name, the table added last wins, so a
+    /// shared table can be overridden with local changes. Fails if the table
+    /// isn't valid, such as when a team's former names aren't listed oldest
+    /// first.
+    pub fn add_toml(&mut self, table: &str) -> Result<()> {
+        let table: AliasTable = ::toml::from_str(table)?;
+
+        self.placeholders.extend(table.placeholders.iter().map(|name| team_key(name)));
+
+        for entry in table.team {
+            let mut names: Vec<TeamName> = vec![];
+
+            for former in entry.former {
+                let until = parse_datetime(&former.until)?;
+
+                // Each name is taken to have been used from the previous
+                // name's `until`, which only works if they're in order
+                if let Some(previous) = names.last().filter(|previous| previous.until.is_some_and(|previous| until <= previous)) {
+                    return Err(Error::InvalidAliasTable(format!(
+                        "the former names of {:?} aren't listed oldest first ({:?} comes after {:?})",
+                        entry.name, former.name, previous.name
+                    )));
+                }
+
+                names.push(TeamName {
+                    name: former.name,
+                    aliases: former.aliases,
+                    until: Some(until)
+                });
+            }
+
+            names.push(TeamName {
+                name: entry.name,
+                aliases: entry.aliases,
+                until: None
+            });
+
+            self.teams.push(CanonicalTeam {
+                id: entry.id.map(TeamId),
+                names
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Whether the name stands in for a team that isn't known yet.
+    pub fn is_placeholder(&self, name: &str) -> bool {
+        let key = team_key(name);
+        PLACEHOLDER_NAMES.contains(&key.as_str()) || self.placeholders.contains(&key)
+    }
+
+    /**
+    Works out which team the given name refers to.
+
+    A name that several teams have gone by (e.g. one that was picked up by a
+    new team after a rebrand) resolves to the team that was using it at the
+    given time, or the one added last if no time is given. Names without an
+    end date are assumed to have always been in use.
+    */
+    pub fn resolve(&self, name: &str, time: Option<DateTime<Utc>>) -> Resolved<'_> {
+        if self.is_placeholder(name) {
+            return Resolved::Placeholder;
+        }
+
+        let key = team_key(name);
+        let mut candidates = self.teams.iter().rev()
+            .filter_map(|team| team.matching_name(&key).map(|index| (team, index)))
+            .peekable();
+        let latest = match candidates.peek() {
+            Some(&(team, _)) => team,
+            None => return Resolved::Unknown
+        };
+
+        // Names that were dropped after the time are a closer match than ones
+        // that are still in use, which might have been picked up since
+        let in_use = time.and_then(|time| {
+            candidates
+                .filter(|&(team, index)| team.in_use(index, time))
+                .min_by_key(|&(team, index)| team.names[index].until.is_none())
+        });
+
+        Resolved::Team(in_use.map_or(latest, |(team, _)| team))
+    }
+
+    /// The team with the given ID, if it's in the alias table.
+    pub fn by_id(&self, id: TeamId) -> Option<&CanonicalTeam> {
+        self.teams.iter().rev().find(|team| team.id == Some(id))
+    }
+
+    /// Rewrites the names of known teams in the given matches to the teams'
+    /// current names, and known teams with placeholder names to `Tbd` (for
+    /// the built-in ones) or `Placeholder`.
+    ///
+    /// Names that aren't in the alias table are only trimmed. Slots for teams
+    /// that aren't known are left alone.
+    pub fn canonicalize(&self, matches: &mut [MatchBriefInfo]) {
+        for _match in matches {
+            let time = _match.scheduled_time;
+
+            for team in _match.teams.iter_mut() {
+                let slot = match team.slot {
+                    TeamSlot::Known(ref name) => match self.resolve(name, time) {
+                        Resolved::Team(canonical) => TeamSlot::Known(canonical.name().into()),
+                        Resolved::Placeholder => match TeamSlot::from_name(name) {
+                            TeamSlot::Known(text) => TeamSlot::Placeholder(text),
+                            slot => slot
+                        },
+                        Resolved::Unknown => TeamSlot::Known(name.trim().into())
+                    },
+                    _ => continue
+                };
+
+                team.slot = slot;
+            }
+        }
+    }
+}
+
+/// Accepts TOML dates, which are taken to be midnight UTC, and date-times
+/// with an offset.
+fn parse_datetime(datetime: &::toml::value::Datetime) -> Result<DateTime<Utc>> {
+    let text = datetime.to_string();
+
+    let parsed = match NaiveDate::parse_from_str(&text, "%Y-%m-%d") {
+        Ok(date) => date.and_hms_opt(0, 0, 0).map(|time| time.and_utc()),
+        Err(_) => DateTime::parse_from_rfc3339(&text).ok().map(|time| time.with_timezone(&Utc))
+    };
+
+    parsed.ok_or_else(|| {
+        Error::InvalidAliasTable(format!("expected a date or a date-time with an offset, found {}", text))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data_structs::MatchId;
+    use chrono::TimeZone;
+
+    const TABLE: &str = r#"
+        placeholders = ["Qualifier"]
+
+        [[team]]
+        name = "Gen.G Esports"
+        id = 12
+        aliases = ["GEN", "Gen.G"]
+
+        [[team.former]]
+        name = "Seoul Dynasty"
+        aliases = ["SEO"]
+        until = 2023-10-01
+
+        [[team]]
+        name = "Dallas Fuel"
+        aliases = ["DAL"]
+    "#;
+
+    fn time(year: i32, month: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).unwrap()
+    }
+
+    fn name<'a>(resolved: Resolved<'a>) -> Option<&'a str> {
+        match resolved {
+            Resolved::Team(team) => Some(team.name()),
+            _ => None
+        }
+    }
+
+    #[test]
+    fn resolves_aliases() {
+        let resolver = TeamResolver::from_toml(TABLE).unwrap();
+
+        assert_eq!(name(resolver.resolve(" gen ", None)), Some("Gen.G Esports"));
+        assert_eq!(name(resolver.resolve("Seoul Dynasty", None)), Some("Gen.G Esports"));
+        assert_eq!(name(resolver.resolve("DAL", None)), Some("Dallas Fuel"));
+        assert_eq!(resolver.resolve("Houston Outlaws", None), Resolved::Unknown);
+
+        assert_eq!(resolver.resolve("TBD", None), Resolved::Placeholder);
+        assert_eq!(resolver.resolve("qualifier", None), Resolved::Placeholder);
+        assert!(!TeamResolver::new().is_placeholder("Qualifier"));
+
+        assert_eq!(resolver.by_id(TeamId(12)).unwrap().name(), "Gen.G Esports");
+        assert_eq!(resolver.by_id(TeamId(13)), None);
+    }
+
+    #[test]
+    fn rebrands() {
+        let mut resolver = TeamResolver::from_toml(TABLE).unwrap();
+        let gen_g = resolver.by_id(TeamId(12)).unwrap();
+
+        assert_eq!(gen_g.name_at(time(2023, 9)), "Seoul Dynasty");
+        assert_eq!(gen_g.name_at(time(2023, 10)), "Gen.G Esports");
+
+        // Another team takes up the old name
+        resolver.add_toml(r#"
+            [[team]]
+            name = "Seoul Dynasty"
+            id = 40
+        "#).unwrap();
+
+        assert_eq!(name(resolver.resolve("Seoul Dynasty", Some(time(2022, 1)))), Some("Gen.G Esports"));
+        assert_eq!(name(resolver.resolve("Seoul Dynasty", Some(time(2024, 1)))), Some("Seoul Dynasty"));
+        assert_eq!(name(resolver.resolve("Seoul Dynasty", None)), Some("Seoul Dynasty"));
+        // Only the old team went by this
+        assert_eq!(name(resolver.resolve("SEO", Some(time(2024, 1)))), Some("Gen.G Esports"));
+    }
+
+    #[test]
+    fn canonicalizes_matches() {
+        let resolver = TeamResolver::from_toml(TABLE).unwrap();
+        let mut _match = MatchBriefInfo {
+            scheduled_time: Some(time(2022, 1)),
+            ..MatchBriefInfo::default()
+        };
+        _match.teams[0].slot = TeamSlot::Known("SEO".into());
+        _match.teams[1].slot = TeamSlot::Known("tba".into());
+
+        let mut other = _match.clone();
+        other.teams[1].slot = TeamSlot::Known(" Houston Outlaws ".into());
+
+        let mut qualifier = _match.clone();
+        qualifier.teams[1].slot = TeamSlot::Known(" Qualifier ".into());
+
+        let mut matches = vec![_match, other, qualifier];
+        resolver.canonicalize(&mut matches);
+
+        assert_eq!(matches[0].teams[0].slot, TeamSlot::Known("Gen.G Esports".into()));
+        assert_eq!(matches[0].teams[1].slot, TeamSlot::Tbd);
+        assert_eq!(matches[0].teams[1].name(), "TBD");
+        assert_eq!(matches[1].teams[1].slot, TeamSlot::Known("Houston Outlaws".into()));
+        assert_eq!(matches[2].teams[1].slot, TeamSlot::Placeholder("Qualifier".into()));
+
+        // Where a team will come from isn't lost
+        let mut bracket = MatchBriefInfo::default();
+        bracket.teams[0].slot = TeamSlot::WinnerOf(MatchId(7));
+
+        let mut matches = vec![bracket];
+        resolver.canonicalize(&mut matches);
+        assert_eq!(matches[0].teams[0].slot, TeamSlot::WinnerOf(MatchId(7)));
+    }
+
+    #[test]
+    fn invalid_tables() {
+        assert!(TeamResolver::from_toml("[[team]]\naliases = []").is_err());
+
+        match TeamResolver::from_toml("[[team]]\nname = \"A\"\n[[team.former]]\nname = \"B\"\nuntil = 12:00:00") {
+            Err(Error::InvalidAliasTable(_)) => {},
+            other => panic!("expected an invalid alias table, got {:?}", other)
+        }
+
+        let out_of_order = r#"
+            [[team]]
+            name = "Gen.G Esports"
+
+            [[team.former]]
+            name = "Seoul Dynasty"
+            until = 2023-10-01
+
+            [[team.former]]
+            name = "KongDoo Panthera"
+            until = 2017-11-01
+        "#;
+
+        match TeamResolver::from_toml(out_of_order) {
+            Err(Error::InvalidAliasTable(reason)) => assert!(reason.contains("\"Gen.G Esports\"")),
+            other => panic!("expected an invalid alias table, got {:?}", other)
+        }
+    }
+}
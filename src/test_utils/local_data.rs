@@ -0,0 +1,181 @@
+//! Helpers for the `test-local-data` tests.
+//!
+//! The tests compare what gets extracted from pages saved in `test_data/`
+//! against expected output stored next to them. Two environment variables
+//! control how that data gets there:
+//!
+//! * `OVERGG_RECORD=1` downloads fresh copies of the pages (requires the
+//!   `http-client` feature) before the tests run against them.
+//! * `OVERGG_BLESS=1` overwrites the expected output with whatever is
+//!   extracted now, instead of failing when the two differ.
+//!
+//! Without either, a mismatch fails the test with a diff of the expected and
+//! actual output.
+
+use crate::data_structs::MatchBriefInfo;
+use crate::site::Site;
+use std::env;
+use std::fmt::Debug;
+use std::fs;
+use std::path::Path;
+use std::sync::Once;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use crate::error::*;
+
+pub const MAIN_PAGE_HTML_PATH: &str = "test_data/www.over.gg.html";
+
+const RECORD_VAR: &str = "OVERGG_RECORD";
+const BLESS_VAR: &str = "OVERGG_BLESS";
+
+#[derive(Debug, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct SaveData {
+    pub match_data: Vec<MatchBriefInfo>,
+    pub winners: Vec<Option<String>>
+}
+
+impl SaveData {
+    pub fn new(matches: Vec<MatchBriefInfo>) -> Self {
+        let winners = matches.iter().map(|t| t.winner().map(|w| w.name().into_owned())).collect();
+
+        Self {
+            match_data: matches,
+            winners
+        }
+    }
+}
+
+fn env_flag(var: &str) -> bool {
+    env::var_os(var).is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Gets the saved main page HTML, recording it first if `OVERGG_RECORD` is
+/// set.
+pub fn main_page_html() -> String {
+    static RECORD: Once = Once::new();
+
+    // Every test wants the page, but it only needs downloading once
+    RECORD.call_once(|| {
+        if env_flag(RECORD_VAR) {
+            record(Site::default().main_page().as_str(), MAIN_PAGE_HTML_PATH).unwrap();
+        }
+    });
+
+    fs::read_to_string(MAIN_PAGE_HTML_PATH).unwrap_or_else(|e| panic!(
+        "Couldn't read {}: {}. Save the over.gg main page there from your browser, \
+         or run the tests with {}=1 to download it.",
+        MAIN_PAGE_HTML_PATH,
+        e,
+        RECORD_VAR
+    ))
+}
+
+#[cfg(feature = "http-client")]
+fn record(url: &str, path: &str) -> Result<()> {
+    use crate::fetch::Fetcher;
+    use crate::http_client::Downloader;
+
+    let page = Downloader::new().fetch(url)?;
+
+    if let Some(dir) = Path::new(path).parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(path, page.body)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "http-client"))]
+fn record(_url: &str, _path: &str) -> Result<()> {
+    panic!("{} requires the `http-client` feature", RECORD_VAR);
+}
+
+/// Checks the given extraction output against the expected output stored at
+/// `path`.
+///
+/// If `OVERGG_BLESS` is set the expected output is overwritten instead.
+pub fn check_expected<P, S>(path: P, actual: &S)
+    where P: AsRef<Path>,
+          S: Serialize + DeserializeOwned + PartialEq + Debug {
+
+    let path = path.as_ref();
+    let actual_json = serde_json::to_string_pretty(actual).unwrap();
+
+    if env_flag(BLESS_VAR) {
+        fs::write(path, actual_json + "\n").unwrap();
+        return;
+    }
+
+    let expected: S = load(path).unwrap_or_else(|e| panic!(
+        "Couldn't load the expected output from {}: {}. Run the tests with {}=1 to generate it.",
+        path.display(),
+        e,
+        BLESS_VAR
+    ));
+
+    if &expected != actual {
+        let expected_json = serde_json::to_string_pretty(&expected).unwrap();
+
+        panic!(
+            "Extraction output differs from {}:\n{}\nIf the change is intended, run the tests \
+             with {}=1 to update the expected output.",
+            path.display(),
+            diff(&expected_json, &actual_json),
+            BLESS_VAR
+        );
+    }
+}
+
+pub fn load<P, S>(path: P) -> Result<S>
+    where P: AsRef<Path>,
+          S: DeserializeOwned {
+
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// A line-based diff, showing only the lines that changed.
+fn diff(expected: &str, actual: &str) -> String {
+    let old: Vec<_> = expected.lines().collect();
+    let new: Vec<_> = actual.lines().collect();
+
+    // Longest common subsequence lengths of every pair of suffixes
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] > lcs[i + 1][j]) {
+            out += &format!("{:>5} + {}\n", j + 1, new[j]);
+            j += 1;
+        } else {
+            out += &format!("{:>5} - {}\n", i + 1, old[i]);
+            i += 1;
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn diff_shows_changed_lines() {
+        assert_eq!(diff("a\nb\nc", "a\nc\nd"), "    2 - b\n    3 + d\n");
+        assert_eq!(diff("a\nb", "a\nc"), "    2 - b\n    2 + c\n");
+    }
+}
@@ -0,0 +1,16 @@
+//! Helpers shared by the crate's tests.
+
+use crate::data_structs::{TeamCompletedMatchBriefInfo, TeamSlot};
+
+#[cfg(feature = "test-local-data")]
+mod local_data;
+#[cfg(feature = "test-local-data")]
+pub use self::local_data::*;
+
+/// A known team with the given name and score.
+pub fn known_team(name: &str, maps_won: Option<u8>) -> TeamCompletedMatchBriefInfo {
+    TeamCompletedMatchBriefInfo {
+        maps_won,
+        slot: TeamSlot::Known(name.into())
+    }
+}
@@ -0,0 +1,82 @@
+//! JavaScript bindings for the scrapers, for use from WebAssembly.
+//!
+//! Build with `wasm-pack build -- --no-default-features --features wasm` (the
+//! `http_client` module does not build for `wasm32-unknown-unknown`). Each
+//! page scraper is exposed as a JS class that is constructed from the page's
+//! HTML (e.g. `document.documentElement.outerHTML`) and hands its data back
+//! as JSON strings in the same shape that `derive-serde` produces.
+
+use crate::data_structs::MatchBriefType;
+use crate::scrapers::main_page::MainPageScraper;
+use crate::scrapers::match_page::MatchPageScraper;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// JS wrapper around `MainPageScraper`.
+#[wasm_bindgen(js_name = MainPageScraper)]
+pub struct JsMainPageScraper {
+    inner: MainPageScraper
+}
+
+#[wasm_bindgen(js_class = MainPageScraper)]
+impl JsMainPageScraper {
+    /// Parses the given main page HTML.
+    #[wasm_bindgen(constructor)]
+    pub fn new(html: String) -> JsMainPageScraper {
+        JsMainPageScraper {
+            inner: html.into()
+        }
+    }
+
+    /// Gets a JSON array of `MatchBriefInfo`s for the given type of match,
+    /// which is one of `"upcoming"`, `"live"` or `"completed"`.
+    #[wasm_bindgen(js_name = matchesBrief)]
+    pub fn matches_brief(&self, match_type: &str) -> Result<String, JsValue> {
+        to_json(&self.inner.matches_brief(parse_match_type(match_type)?))
+    }
+
+    /// Gets the `Livestreams` on the page as a JSON object.
+    #[wasm_bindgen(js_name = liveStreams)]
+    pub fn live_streams(&self) -> Result<String, JsValue> {
+        to_json(&self.inner.live_streams())
+    }
+}
+
+/// JS wrapper around `MatchPageScraper`.
+#[wasm_bindgen(js_name = MatchPageScraper)]
+pub struct JsMatchPageScraper {
+    inner: MatchPageScraper
+}
+
+#[wasm_bindgen(js_class = MatchPageScraper)]
+impl JsMatchPageScraper {
+    /// Parses the given match page HTML.
+    #[wasm_bindgen(constructor)]
+    pub fn new(html: String) -> JsMatchPageScraper {
+        JsMatchPageScraper {
+            inner: html.into()
+        }
+    }
+
+    /// Gets the match's `LiveMatchState` as a JSON object.
+    #[wasm_bindgen(js_name = liveState)]
+    pub fn live_state(&self) -> Result<String, JsValue> {
+        to_json(&self.inner.live_state())
+    }
+}
+
+fn parse_match_type(match_type: &str) -> Result<MatchBriefType, JsValue> {
+    match match_type {
+        "upcoming" => Ok(MatchBriefType::InFuture),
+        "live" => Ok(MatchBriefType::Live),
+        "completed" => Ok(MatchBriefType::Completed),
+        other => Err(JsValue::from_str(&format!(
+            "unknown match type {:?}, expected \"upcoming\", \"live\" or \"completed\"",
+            other
+        )))
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, JsValue> {
+    ::serde_json::to_string(value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
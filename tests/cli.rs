@@ -0,0 +1,98 @@
+//! Runs the `overgg` binary against the pages in `tests/fixtures/` to check
+//! its output and exit codes.
+
+#![cfg(feature = "cli")]
+
+use std::process::{Command, Output};
+
+const MAIN_PAGE: &str = "tests/fixtures/main_page.html";
+const LIVE_MATCH_PAGE: &str = "tests/fixtures/match_page_live.html";
+const TEAM_PAGE: &str = "tests/fixtures/team_page.html";
+const EVENT_PAGE: &str = "tests/fixtures/event_page.html";
+
+fn overgg(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_overgg"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn prints_matches() {
+    let output = overgg(&["--from-file", MAIN_PAGE, "--format", "csv", "matches", "--completed"]);
+
+    assert_eq!(output.status.code(), Some(0));
+    let csv = stdout(&output);
+    assert!(csv.starts_with("event_name,event_series,team1_name"));
+    assert_eq!(csv.lines().count(), 4);
+}
+
+#[test]
+fn prints_match_maps() {
+    let output = overgg(&["match", "1001", "--from-file", LIVE_MATCH_PAGE]);
+
+    assert_eq!(output.status.code(), Some(0));
+    let table = stdout(&output);
+    assert!(table.starts_with("EVENT_NAME"));
+    assert!(table.contains("Watchpoint: Gibraltar"));
+    assert!(table.contains("in_progress"));
+}
+
+#[test]
+fn prints_team_and_event_matches() {
+    let team = overgg(&["team", "1", "--from-file", TEAM_PAGE, "--format", "csv", "--strict"]);
+
+    assert_eq!(team.status.code(), Some(0));
+    let csv = stdout(&team);
+    assert!(csv.starts_with("event_name,event_series,team1_name"));
+    assert_eq!(csv.lines().count(), 5);
+    assert!(csv.contains(",990\n"));
+
+    let event = overgg(&["--from-file", EVENT_PAGE, "--format", "json", "event", "10"]);
+
+    assert_eq!(event.status.code(), Some(0));
+    let json = stdout(&event);
+    assert!(json.contains("\"name\": \"Example Cup\""));
+    assert!(json.contains("\"id\": 10"));
+}
+
+#[test]
+fn help_exits_successfully() {
+    let output = overgg(&["--help"]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).starts_with("Query data from over.gg"));
+}
+
+#[test]
+fn invalid_arguments_exit_with_2() {
+    let output = overgg(&["matches"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("error: matches requires one of"));
+    assert!(stderr(&output).contains("USAGE:"));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn failures_exit_with_1() {
+    let missing = overgg(&["--from-file", "tests/fixtures/missing.html", "streams"]);
+    assert_eq!(missing.status.code(), Some(1));
+    assert!(stderr(&missing).starts_with("error: "));
+
+    // The main page doesn't have anything a match page should
+    let strict = overgg(&["--from-file", MAIN_PAGE, "--strict", "match", "1001"]);
+    assert_eq!(strict.status.code(), Some(1));
+    assert!(strict.stdout.is_empty());
+
+    let team = overgg(&["--from-file", LIVE_MATCH_PAGE, "--strict", "team", "1"]);
+    assert_eq!(team.status.code(), Some(1));
+}
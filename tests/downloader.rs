@@ -0,0 +1,241 @@
+//! Tests `http_client::Downloader` against a local mock of the site (see
+//! `support/mod.rs`).
+//!
+//! `Downloader` doesn't retry or cache anything, so every fetch should make
+//! exactly one request and hand failures straight back.
+
+#![cfg(feature = "http-client")]
+
+extern crate overgg_scraper;
+extern crate reqwest;
+
+mod support;
+
+use overgg_scraper::crawler::Crawler;
+use overgg_scraper::data_structs::MatchBriefType::*;
+use overgg_scraper::data_structs::{EventId, MatchId, PlayerId, TeamId};
+use overgg_scraper::error::Error;
+use overgg_scraper::fetch::Fetcher;
+use overgg_scraper::head_to_head::HeadToHead;
+use overgg_scraper::http_client::Downloader;
+use overgg_scraper::scrapers::main_page::MainPageScraper;
+use overgg_scraper::site::Site;
+use overgg_scraper::stats::Record;
+use std::time::Duration;
+use support::{MockServer, Response};
+
+#[test]
+fn fetches_pages() {
+    let server = MockServer::start();
+    let page = Downloader::new().fetch(&server.url("/")).unwrap();
+
+    assert_eq!(page.url, server.url("/"));
+    assert!(page.headers.contains(&("content-type".into(), "text/html; charset=utf-8".into())));
+    assert_eq!(server.hits("/"), 1);
+
+    let scraper = MainPageScraper::from(page.body);
+    assert_eq!(scraper.matches_brief_strict(Completed).unwrap().len(), 3);
+
+    // Query strings are part of what gets requested
+    let results = Downloader::new().fetch(&server.url("/matches/results?page=2")).unwrap();
+    assert!(results.body.contains("Kilo vs Lima"));
+    assert_eq!(server.hits("/matches/results"), 0);
+}
+
+#[test]
+fn main_page_from_another_site() {
+    let server = MockServer::start();
+    server.respond("/", Response::Page(r#"
+        <div class="js-home-matches-completed">
+            <a class="wf-module-item mod-match" href="/1001/alpha-vs-bravo">
+                <div class="h-match-team"><div class="h-match-team-name">Alpha</div></div>
+            </a>
+        </div>
+        <div class="js-home-streams">
+            <a class="wf-module-item mod-disc" href="/stream/1">
+                <span class="stream-item-txt-name">Hosted</span>
+            </a>
+        </div>
+    "#.into()));
+
+    let dl = Downloader::new().with_site(Site::new(&server.base_url()).unwrap());
+    let scraper = dl.main_page().unwrap();
+    assert_eq!(server.hits("/"), 1);
+
+    // Links are resolved against the site the page came from
+    assert_eq!(scraper.matches_brief(Completed)[0].id, Some(MatchId(1001)));
+    assert_eq!(scraper.live_streams().other_top[0].url.as_ref().unwrap().as_str(), server.url("/stream/1"));
+    assert_eq!(dl.site().match_page(MatchId(1001)).as_str(), server.url("/1001"));
+}
+
+#[test]
+fn live_match_page() {
+    let server = MockServer::start();
+    let dl = Downloader::new().with_site(Site::new(&server.base_url()).unwrap());
+
+    // The site redirects to the page's path with its slug
+    let state = dl.match_page(MatchId(1001)).unwrap().live_state_strict().unwrap();
+    assert_eq!(server.hits("/1001"), 1);
+    assert_eq!(server.hits("/1001/alpha-vs-bravo-example-cup-grand-final"), 1);
+
+    assert_eq!(state.id, Some(MatchId(1001)));
+    assert!(state.is_live);
+    assert_eq!(state.teams[0].maps_won, Some(2));
+    assert_eq!(state.current_map().unwrap().scores, [Some(1), Some(2)]);
+
+    match dl.match_page(MatchId(1003)) {
+        Err(Error::NonSuccessStatus(status)) => assert_eq!(status.as_u16(), 404),
+        other => panic!("expected a 404, got {:?}", other.map(|s| s.live_state()))
+    }
+}
+
+#[test]
+fn team_and_event_pages() {
+    let server = MockServer::start();
+    let dl = Downloader::new().with_site(Site::new(&server.base_url()).unwrap());
+
+    let team = dl.team_page(TeamId(1)).unwrap().team_info_strict().unwrap();
+    assert_eq!(server.hits("/team/1"), 1);
+    assert_eq!(team.id, Some(TeamId(1)));
+    assert_eq!(team.name, "Alpha");
+    assert_eq!(team.roster[1].id, Some(PlayerId(101)));
+    assert_eq!(team.matches.len(), 4);
+    assert_eq!(team.matches[1].id, Some(MatchId(990)));
+
+    let event = dl.event_page(EventId(10)).unwrap().event_info_strict().unwrap();
+    assert_eq!(server.hits("/event/10"), 1);
+    assert_eq!(event.id, Some(EventId(10)));
+    assert_eq!(event.name, "Example Cup");
+    assert_eq!(event.matches.len(), 2);
+}
+
+#[test]
+fn head_to_head_from_team_pages() {
+    let server = MockServer::start();
+    server.respond("/team/2", Response::Page(r#"
+        <h1 class="wf-title">Bravo</h1>
+        <a class="m-item" href="/1001/alpha-vs-bravo-example-cup-grand-final">
+            <div class="m-item-event">Example Cup</div>
+            <div class="m-item-series">Grand Final</div>
+            <div class="m-item-team"><span class="m-item-team-name">Alpha</span><span class="m-item-team-score">2</span></div>
+            <div class="m-item-team"><span class="m-item-team-name">Bravo</span><span class="m-item-team-score">1</span></div>
+            <div class="m-item-date" data-utc-ts="1533600000"></div>
+        </a>
+        <a class="m-item" href="/960/bravo-vs-alpha-example-league-week-0">
+            <div class="m-item-event">Example League</div>
+            <div class="m-item-series">Week 0</div>
+            <div class="m-item-team"><span class="m-item-team-name">Bravo</span><span class="m-item-team-score">3</span></div>
+            <div class="m-item-team"><span class="m-item-team-name">Alpha</span><span class="m-item-team-score">1</span></div>
+            <div class="m-item-date" data-utc-ts="1532000000"></div>
+        </a>
+    "#.into()));
+
+    let site = Site::new(&server.base_url()).unwrap();
+    let h2h = HeadToHead::collect(Downloader::new(), &site, TeamId(1), TeamId(2)).unwrap();
+
+    assert_eq!((server.hits("/team/1"), server.hits("/team/2")), (1, 1));
+    assert_eq!((h2h.team.as_str(), h2h.opponent.as_str()), ("Alpha", "Bravo"));
+    assert_eq!((h2h.team_id, h2h.opponent_id), (Some(TeamId(1)), Some(TeamId(2))));
+
+    // 1001 is on both pages but only counts once
+    let ids: Vec<Option<MatchId>> = h2h.meetings.iter().map(|m| m.id).collect();
+    assert_eq!(ids, vec![Some(MatchId(1001)), Some(MatchId(990)), Some(MatchId(970)), Some(MatchId(960))]);
+    assert_eq!(h2h.series, Record { wins: 2, losses: 2, draws: 0 });
+    assert_eq!(h2h.maps, Record { wins: 8, losses: 7, draws: 0 });
+
+    server.not_found("/team/2");
+    assert!(HeadToHead::collect(Downloader::new(), &site, TeamId(1), TeamId(2)).is_err());
+}
+
+#[test]
+fn error_statuses() {
+    let server = MockServer::start();
+    server.not_found("/team/1/alpha").rate_limited("/", 30);
+    let dl = Downloader::new();
+
+    match dl.fetch(&server.url("/team/1/alpha")) {
+        Err(Error::NonSuccessStatus(status)) => assert_eq!(status.as_u16(), 404),
+        other => panic!("expected a 404, got {:?}", other)
+    }
+
+    // `Retry-After` isn't honored; the 429 is returned as is
+    match dl.fetch(&server.url("/")) {
+        Err(Error::NonSuccessStatus(status)) => assert_eq!(status.as_u16(), 429),
+        other => panic!("expected a 429, got {:?}", other)
+    }
+    assert_eq!(server.hits("/"), 1);
+
+    // Paths the site doesn't have are 404s too
+    assert!(dl.fetch(&server.url("/nothing-here")).is_err());
+}
+
+#[test]
+fn slow_responses_time_out() {
+    let server = MockServer::start();
+    server.slow("/", Duration::from_secs(2));
+
+    let client = reqwest::Client::builder().timeout(Duration::from_millis(200)).build().unwrap();
+
+    match Downloader::with_client(client).fetch(&server.url("/")) {
+        Err(Error::Req(_)) => {},
+        other => panic!("expected the request to time out, got {:?}", other)
+    }
+}
+
+#[test]
+fn malformed_html() {
+    let server = MockServer::start();
+    server.malformed("/");
+
+    let page = Downloader::new().fetch(&server.url("/")).unwrap();
+    let scraper = MainPageScraper::from(page.body);
+
+    // What's there still gets extracted, with warnings about the rest
+    let extracted = scraper.matches_brief_with_warnings(Completed);
+    assert_eq!(extracted.value.len(), 1);
+    assert_eq!(extracted.value[0].teams[0].name(), "Cut off");
+    assert!(!extracted.is_clean());
+}
+
+#[test]
+fn crawls_the_site() {
+    let server = MockServer::start();
+    let site = Site::new(&server.base_url()).unwrap();
+    let crawler = Crawler::from_site(Downloader::new(), &site)
+        .max_depth(2)
+        .delay(Duration::from_secs(0));
+
+    let mut urls = vec![];
+    for page in crawler {
+        match page {
+            Ok(page) => urls.push(page.page.url),
+            // Not every linked page exists on the mock site
+            Err(Error::NonSuccessStatus(status)) => assert_eq!(status.as_u16(), 404),
+            Err(e) => panic!("unexpected error: {}", e)
+        }
+    }
+
+    assert!(urls.contains(&server.url("/1001/alpha-vs-bravo-example-cup-grand-final")));
+    assert!(urls.contains(&server.url("/team/1/alpha")));
+
+    // Each page is only requested once
+    assert_eq!(server.hits("/"), 1);
+    assert_eq!(server.hits("/1001/alpha-vs-bravo-example-cup-grand-final"), 1);
+    assert_eq!(server.hits("/team/1/alpha"), 1);
+    assert_eq!(server.hits("/event/10/example-cup"), 1);
+}
+
+#[test]
+fn crawls_the_results_archive() {
+    let server = MockServer::start();
+    let site = Site::new(&server.base_url()).unwrap();
+    let crawler = Crawler::from_results_archive(Downloader::new(), &site, 1)
+        .max_depth(0)
+        .delay(Duration::from_secs(0));
+
+    let urls: Vec<String> = crawler.map(|page| page.unwrap().page.url).collect();
+    assert_eq!(urls, vec![server.url("/matches/results"), server.url("/matches/results?page=2")]);
+
+    // Page 2 links back to page 1, which isn't fetched again
+    assert_eq!(server.hits("/matches/results"), 1);
+}
@@ -0,0 +1,256 @@
+//! Property tests that run the main page extractors over randomized variants
+//! of the card markup: missing nodes, extra whitespace, non-numeric scores and
+//! viewer counts, and out of range timestamps.
+//!
+//! Whatever the markup, extraction must not panic and has to agree with what
+//! the markup says.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+extern crate chrono;
+extern crate overgg_scraper;
+extern crate proptest;
+
+use chrono::{LocalResult, TimeZone, Utc};
+use overgg_scraper::data_structs::MatchBriefType::{self, *};
+use overgg_scraper::scrapers::main_page::MainPageScraper;
+use proptest::prelude::*;
+
+#[derive(Debug, Clone)]
+struct TeamCard {
+    name: Option<String>,
+    score: Option<String>
+}
+
+#[derive(Debug, Clone)]
+struct MatchCard {
+    href: Option<String>,
+    event: Option<String>,
+    series: Option<String>,
+    teams: Vec<TeamCard>,
+    /// Whether the time element is there, and its `data-utc-ts` if it has one
+    time: Option<Option<String>>,
+    live: bool
+}
+
+#[derive(Debug, Clone)]
+struct StreamCard {
+    name: Option<String>,
+    viewer_count: Option<String>,
+    href: Option<String>,
+    title: Option<String>,
+    flag: bool
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn padded<S: Strategy<Value = String>>(inner: S) -> impl Strategy<Value = String> {
+    ("[ \t\n]{0,3}", inner, "[ \t\n]{0,3}").prop_map(|(before, text, after)| before + &text + &after)
+}
+
+fn text() -> impl Strategy<Value = String> {
+    "\\PC{0,12}"
+}
+
+fn number_like() -> impl Strategy<Value = String> {
+    padded(prop_oneof![
+        (0u64..100_000).prop_map(|n| n.to_string()),
+        any::<i64>().prop_map(|n| n.to_string()),
+        Just("-".to_string()),
+        Just("–".to_string()),
+        text()
+    ])
+}
+
+fn team_card() -> impl Strategy<Value = TeamCard> {
+    (proptest::option::of(padded(text())), proptest::option::of(number_like()))
+        .prop_map(|(name, score)| TeamCard { name, score })
+}
+
+fn match_card() -> impl Strategy<Value = MatchCard> {
+    (
+        proptest::option::of(prop_oneof![
+            (0u32..1_000_000).prop_map(|id| format!("/{}/a-vs-b", id)),
+            text()
+        ]),
+        proptest::option::of(padded(text())),
+        proptest::option::of(padded(text())),
+        proptest::collection::vec(team_card(), 0..4),
+        proptest::option::of(proptest::option::of(number_like())),
+        any::<bool>()
+    ).prop_map(|(href, event, series, teams, time, live)| MatchCard { href, event, series, teams, time, live })
+}
+
+fn stream_card() -> impl Strategy<Value = StreamCard> {
+    (
+        proptest::option::of(padded(text())),
+        proptest::option::of(number_like()),
+        proptest::option::of(text()),
+        proptest::option::of(text()),
+        any::<bool>()
+    ).prop_map(|(name, viewer_count, href, title, flag)| StreamCard { name, viewer_count, href, title, flag })
+}
+
+fn render_match(card: &MatchCard) -> String {
+    let mut html = String::from(r#"<a class="wf-module-item mod-match""#);
+    if let Some(ref href) = card.href {
+        html += &format!(r#" href="{}""#, escape(href));
+    }
+    html += ">";
+
+    if let Some(ref event) = card.event {
+        html += &format!(r#"<div class="h-match-preview-event">{}</div>"#, escape(event));
+    }
+    if let Some(ref series) = card.series {
+        html += &format!(r#"<div class="h-match-preview-series">{}</div>"#, escape(series));
+    }
+
+    for team in &card.teams {
+        html += r#"<div class="h-match-team">"#;
+        if let Some(ref name) = team.name {
+            html += &format!(r#"<div class="h-match-team-name">{}</div>"#, escape(name));
+        }
+        if let Some(ref score) = team.score {
+            html += &format!(r#"<div class="h-match-team-score mod-count">{}</div>"#, escape(score));
+        }
+        html += "</div>";
+    }
+
+    match card.time {
+        Some(Some(ref ts)) => html += &format!(r#"<div class="h-match-preview-time" data-utc-ts="{}"></div>"#, escape(ts)),
+        Some(None) => html += r#"<div class="h-match-preview-time"></div>"#,
+        None => {}
+    }
+
+    if card.live {
+        html += r#"<div class="h-match-eta mod-live">LIVE</div>"#;
+    }
+
+    html + "</a>"
+}
+
+fn render_stream(card: &StreamCard) -> String {
+    let mut html = String::from(r#"<a class="wf-module-item mod-disc""#);
+    if let Some(ref href) = card.href {
+        html += &format!(r#" href="{}""#, escape(href));
+    }
+    if let Some(ref title) = card.title {
+        html += &format!(r#" title="{}""#, escape(title));
+    }
+    html += ">";
+
+    if card.flag {
+        html += r#"<i class="flag module-item-flag"></i>"#;
+    }
+    if let Some(ref name) = card.name {
+        html += &format!(r#"<span class="stream-item-txt-name">{}</span>"#, escape(name));
+    }
+    if let Some(ref count) = card.viewer_count {
+        html += &format!(r#"<div class="stream-item-count">{}</div>"#, escape(count));
+    }
+
+    html + "</a>"
+}
+
+fn render_page(upcoming: &[MatchCard], completed: &[MatchCard], streams: &[StreamCard]) -> String {
+    let list = |class: &str, cards: String| format!(r#"<div class="wf-module {}">{}</div>"#, class, cards);
+
+    format!(
+        "<html><body>{}{}{}</body></html>",
+        list("js-home-matches-upcoming", upcoming.iter().map(render_match).collect()),
+        list("js-home-matches-completed", completed.iter().map(render_match).collect()),
+        list("js-home-streams", streams.iter().map(render_stream).collect())
+    )
+}
+
+fn check_matches(scraper: &MainPageScraper, _type: MatchBriefType, cards: Vec<&MatchCard>) -> Result<(), TestCaseError> {
+    let extracted = scraper.matches_brief_with_warnings(_type);
+
+    // The three flavors have to agree with each other
+    prop_assert_eq!(&scraper.matches_brief(_type), &extracted.value);
+    prop_assert_eq!(scraper.matches_brief_strict(_type).is_ok(), extracted.is_clean());
+
+    prop_assert_eq!(extracted.value.len(), cards.len());
+
+    for (_match, card) in extracted.value.iter().zip(cards) {
+        for (i, team) in _match.teams.iter().enumerate() {
+            let expected = card.teams.get(i)
+                .and_then(|t| t.score.as_ref())
+                .and_then(|s| s.trim().parse::<u8>().ok());
+            prop_assert_eq!(team.maps_won, expected);
+        }
+
+        let expected_time = match card.time {
+            Some(Some(ref ts)) => match ts.trim().parse() {
+                Ok(ts) => match Utc.timestamp_opt(ts, 0) {
+                    LocalResult::Single(time) => Some(time),
+                    _ => None
+                },
+                Err(_) => None
+            },
+            _ => None
+        };
+        prop_assert_eq!(_match.scheduled_time, expected_time);
+
+        // There's either a winner and a loser, or neither
+        match (_match.winner(), _match.loser()) {
+            (Some(winner), Some(loser)) => {
+                prop_assert!(!::std::ptr::eq(winner, loser));
+                prop_assert!(winner.maps_won > loser.maps_won);
+            },
+            (None, None) => prop_assert_eq!(_match.teams[0].maps_won, _match.teams[1].maps_won),
+            _ => prop_assert!(false, "a winner without a loser or vice versa")
+        }
+    }
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn matches_brief(
+        upcoming in proptest::collection::vec(match_card(), 0..5),
+        completed in proptest::collection::vec(match_card(), 0..5)
+    ) {
+        let scraper = MainPageScraper::from(render_page(&upcoming, &completed, &[]));
+
+        check_matches(&scraper, Live, upcoming.iter().filter(|c| c.live).collect())?;
+        check_matches(&scraper, InFuture, upcoming.iter().filter(|c| !c.live).collect())?;
+        check_matches(&scraper, Completed, completed.iter().collect())?;
+    }
+
+    #[test]
+    fn live_streams(streams in proptest::collection::vec(stream_card(), 0..6)) {
+        let scraper = MainPageScraper::from(render_page(&[], &[], &streams));
+        let extracted = scraper.live_streams_with_warnings();
+
+        prop_assert_eq!(&scraper.live_streams(), &extracted.value);
+        prop_assert_eq!(scraper.live_streams_strict().is_ok(), extracted.is_clean());
+
+        let (curated, other_top): (Vec<_>, Vec<_>) = streams.iter().partition(|s| s.flag);
+        prop_assert_eq!(extracted.value.curated.len(), curated.len());
+        prop_assert_eq!(extracted.value.other_top.len(), other_top.len());
+
+        let extracted_streams = extracted.value.curated.iter().zip(curated)
+            .chain(extracted.value.other_top.iter().zip(other_top));
+        for (stream, card) in extracted_streams {
+            let expected = card.viewer_count.as_ref().and_then(|c| c.trim().parse::<u32>().ok());
+            prop_assert_eq!(stream.viewer_count, expected);
+        }
+    }
+
+    #[test]
+    fn arbitrary_html_does_not_panic(html in "\\PC{0,200}") {
+        let scraper = MainPageScraper::from(html);
+
+        for &_type in &[Live, InFuture, Completed] {
+            let _ = scraper.matches_brief_with_warnings(_type);
+        }
+        let _ = scraper.live_streams_with_warnings();
+    }
+}
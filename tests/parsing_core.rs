@@ -0,0 +1,46 @@
+//! The parsing core has to be usable with HTML supplied by the caller, with or
+//! without the `http-client` feature. CI runs this under every feature
+//! combination (see `.github/workflows/ci.yml`).
+
+extern crate overgg_scraper;
+
+use overgg_scraper::data_structs::MatchBriefType::*;
+use overgg_scraper::error::Error;
+use overgg_scraper::scrapers::main_page::MainPageScraper;
+
+const PAGE: &str = r#"
+    <div class="js-home-matches-upcoming">
+        <a class="wf-module-item mod-match" href="/2">
+            <div class="h-match-preview-event">Event</div>
+            <div class="h-match-preview-series">Series</div>
+            <div class="h-match-team"><div class="h-match-team-name">Team A</div></div>
+            <div class="h-match-team"><div class="h-match-team-name">Team B</div></div>
+            <div class="h-match-preview-time" data-utc-ts="1533600000"></div>
+        </a>
+    </div>
+    <div class="js-home-streams">
+        <a class="wf-module-item mod-disc" href="https://www.twitch.tv/a" title="Scrims">
+            <i class="module-item-flag"></i>
+            <span class="stream-item-txt-name">a</span>
+            <div class="stream-item-count">lots</div>
+        </a>
+    </div>
+"#;
+
+#[test]
+fn parses_caller_supplied_html() {
+    let scraper = MainPageScraper::from(String::from(PAGE));
+
+    let upcoming = scraper.matches_brief_strict(InFuture).unwrap();
+    assert_eq!(upcoming.len(), 1);
+    assert_eq!(upcoming[0].teams[1].name(), "Team B");
+    assert_eq!(upcoming[0].scheduled_time.unwrap().timestamp(), 1_533_600_000);
+
+    let streams = scraper.live_streams();
+    assert_eq!(streams.curated[0].title, Some("Scrims".into()));
+
+    match scraper.live_streams_strict() {
+        Err(Error::Extraction(issue)) => assert_eq!(issue.path, "streams[0].viewer_count"),
+        other => panic!("expected an extraction error, got {:?}", other)
+    }
+}
@@ -0,0 +1,231 @@
+//! A local stand-in for over.gg, so the HTTP side of the crate can be tested
+//! end-to-end without the network.
+//!
+//! `MockServer::start` serves synthetic pages at the same paths as the real
+//! site: the main page, a match page, team and event pages (all from
+//! `tests/fixtures/`), and paginated results. Like the real site, the bare
+//! ID paths of match, team and event pages redirect to the path with the
+//! slug. Individual paths can be made to fail with a 404, a 429 with a
+//! `Retry-After` header, a slow response or malformed HTML, and the server
+//! counts the requests it gets for each path.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+pub const MAIN_PAGE: &str = include_str!("../fixtures/main_page.html");
+
+/// The page of the live match on the main page.
+pub const LIVE_MATCH_PAGE: &str = include_str!("../fixtures/match_page_live.html");
+
+pub const TEAM_PAGE: &str = include_str!("../fixtures/team_page.html");
+
+pub const EVENT_PAGE: &str = include_str!("../fixtures/event_page.html");
+
+pub const RESULTS_PAGE_1: &str = r#"<html><body>
+    <a class="wf-module-item" href="/998/golf-vs-hotel-example-league-week-2">Golf vs Hotel</a>
+    <a class="wf-module-item" href="/997/india-vs-juliet-example-league-week-2">India vs Juliet</a>
+    <a class="action-container-pages" href="/matches/results?page=2">Next</a>
+</body></html>"#;
+
+pub const RESULTS_PAGE_2: &str = r#"<html><body>
+    <a class="wf-module-item" href="/996/kilo-vs-lima-example-open-qualifier">Kilo vs Lima</a>
+    <a class="action-container-pages" href="/matches/results">Previous</a>
+</body></html>"#;
+
+/// HTML that stops partway through a match card.
+pub const MALFORMED: &str = r#"<html><body><div class="js-home-matches-completed">
+    <a class="wf-module-item mod-match" href="/999/cut-off"><div class="h-match-team"><div class="h-match-team-name">Cut off"#;
+
+/// How the server responds to a request for a path.
+#[derive(Debug, Clone)]
+pub enum Response {
+    Page(String),
+    /// 301 Moved Permanently, to the given path
+    Redirect(String),
+    NotFound,
+    /// 429 Too Many Requests, with a `Retry-After` in seconds
+    RateLimited(u32),
+    /// The response is only sent after waiting this long
+    Slow(Duration, String)
+}
+
+struct Shared {
+    routes: HashMap<String, Response>,
+    hits: HashMap<String, usize>
+}
+
+pub struct MockServer {
+    addr: SocketAddr,
+    shared: Arc<Mutex<Shared>>,
+    stop: Arc<AtomicBool>
+}
+
+impl MockServer {
+    /// Starts a server on a free local port, serving the pages of a small
+    /// synthetic site.
+    pub fn start() -> Self {
+        let mut routes = HashMap::new();
+        let mut page = |path: &str, html: &str| {
+            routes.insert(path.to_string(), Response::Page(html.into()));
+        };
+
+        page("/", MAIN_PAGE);
+        page("/1001/alpha-vs-bravo-example-cup-grand-final", LIVE_MATCH_PAGE);
+        page("/team/1/alpha", TEAM_PAGE);
+        page("/event/10/example-cup", EVENT_PAGE);
+        page("/matches/results", RESULTS_PAGE_1);
+        page("/matches/results?page=2", RESULTS_PAGE_2);
+        routes.insert("/1001".into(), Response::Redirect("/1001/alpha-vs-bravo-example-cup-grand-final".into()));
+        routes.insert("/team/1".into(), Response::Redirect("/team/1/alpha".into()));
+        routes.insert("/event/10".into(), Response::Redirect("/event/10/example-cup".into()));
+
+        let shared = Arc::new(Mutex::new(Shared {
+            routes,
+            hits: HashMap::new()
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        {
+            let shared = shared.clone();
+            let stop = stop.clone();
+
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stop.load(Ordering::SeqCst) {
+                        break;
+                    }
+
+                    if let Ok(stream) = stream {
+                        let shared = shared.clone();
+                        thread::spawn(move || handle(stream, &shared));
+                    }
+                }
+            });
+        }
+
+        Self {
+            addr,
+            shared,
+            stop
+        }
+    }
+
+    /// The server's origin, e.g. `http://127.0.0.1:12345/`.
+    pub fn base_url(&self) -> String {
+        format!("http://{}/", self.addr)
+    }
+
+    /// The full URL of the given path (which should start with `/`).
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    /// Changes how the server responds to requests for the given path.
+    pub fn respond(&self, path: &str, response: Response) -> &Self {
+        self.shared.lock().unwrap().routes.insert(path.into(), response);
+        self
+    }
+
+    pub fn not_found(&self, path: &str) -> &Self {
+        self.respond(path, Response::NotFound)
+    }
+
+    pub fn rate_limited(&self, path: &str, retry_after_secs: u32) -> &Self {
+        self.respond(path, Response::RateLimited(retry_after_secs))
+    }
+
+    /// Delays the response for the given path, keeping its current page.
+    pub fn slow(&self, path: &str, delay: Duration) -> &Self {
+        let page = match self.shared.lock().unwrap().routes.get(path) {
+            Some(Response::Page(html)) | Some(Response::Slow(_, html)) => html.clone(),
+            _ => String::new()
+        };
+
+        self.respond(path, Response::Slow(delay, page))
+    }
+
+    pub fn malformed(&self, path: &str) -> &Self {
+        self.respond(path, Response::Page(MALFORMED.into()))
+    }
+
+    /// How many requests the server has received for the given path.
+    pub fn hits(&self, path: &str) -> usize {
+        self.shared.lock().unwrap().hits.get(path).cloned().unwrap_or(0)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // Wake the accept loop up so it notices
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+fn handle(mut stream: TcpStream, shared: &Mutex<Shared>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+
+    // We don't care about the headers, but they have to be read
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) if line == "\r\n" || line == "\n" => break,
+            Ok(_) => {}
+        }
+    }
+
+    let path = match request_line.split_whitespace().nth(1) {
+        Some(path) => path.to_string(),
+        None => return
+    };
+
+    let response = {
+        let mut shared = shared.lock().unwrap();
+        *shared.hits.entry(path.clone()).or_insert(0) += 1;
+        shared.routes.get(&path).cloned().unwrap_or(Response::NotFound)
+    };
+
+    let (status, extra_headers, body) = match response {
+        Response::Page(html) => ("200 OK", String::new(), html),
+        Response::Redirect(location) => (
+            "301 Moved Permanently",
+            format!("Location: {}\r\n", location),
+            String::new()
+        ),
+        Response::NotFound => ("404 Not Found", String::new(), "<html><body>Page not found</body></html>".into()),
+        Response::RateLimited(secs) => (
+            "429 Too Many Requests",
+            format!("Retry-After: {}\r\n", secs),
+            "<html><body>Slow down</body></html>".into()
+        ),
+        Response::Slow(delay, html) => {
+            thread::sleep(delay);
+            ("200 OK", String::new(), html)
+        }
+    };
+
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n{}",
+        status,
+        body.len(),
+        extra_headers,
+        body
+    );
+}
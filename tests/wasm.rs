@@ -0,0 +1,57 @@
+//! Run with `wasm-pack test --node -- --no-default-features --features wasm`.
+
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+extern crate overgg_scraper;
+extern crate wasm_bindgen_test;
+
+use overgg_scraper::wasm::{JsMainPageScraper, JsMatchPageScraper};
+use wasm_bindgen_test::*;
+
+const PAGE: &str = r#"
+    <div class="js-home-matches-completed">
+        <a class="wf-module-item mod-match" href="/1">
+            <div class="h-match-preview-event">Event</div>
+            <div class="h-match-preview-series">Series</div>
+            <div class="h-match-team">
+                <div class="h-match-team-name">Team A</div>
+                <div class="h-match-team-score mod-count">3</div>
+            </div>
+            <div class="h-match-team">
+                <div class="h-match-team-name">Team B</div>
+                <div class="h-match-team-score mod-count">1</div>
+            </div>
+        </a>
+    </div>
+"#;
+
+#[wasm_bindgen_test]
+fn matches_brief_json() {
+    let scraper = JsMainPageScraper::new(PAGE.into());
+    let json = scraper.matches_brief("completed").unwrap();
+
+    assert!(json.contains("\"slot\":{\"Known\":\"Team A\"}"));
+    assert!(json.contains("\"maps_won\":3"));
+    assert!(scraper.matches_brief("yesterday").is_err());
+}
+
+#[wasm_bindgen_test]
+fn live_streams_json() {
+    let scraper = JsMainPageScraper::new(PAGE.into());
+
+    assert_eq!(scraper.live_streams().unwrap(), r#"{"curated":[],"other_top":[]}"#);
+}
+
+#[wasm_bindgen_test]
+fn live_state_json() {
+    let scraper = JsMatchPageScraper::new(r#"
+        <div class="game-map mod-live">
+            <div class="game-map-name">Ilios</div>
+            <span class="game-map-score">1</span>
+            <span class="game-map-score">0</span>
+        </div>
+    "#.into());
+    let json = scraper.live_state().unwrap();
+
+    assert!(json.contains(r#""maps":[{"name":"Ilios","scores":[1,0],"status":"InProgress"}]"#));
+}